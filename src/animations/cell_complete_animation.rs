@@ -3,7 +3,6 @@ use crate::colors;
 use crate::geometry::Position;
 use core::fmt::Display;
 use serde::Serialize;
-use wasm_bindgen::prelude::*;
 
 #[derive(Serialize)]
 pub struct CellCompleteAnimation {
@@ -19,9 +18,9 @@ impl CellCompleteAnimation {
     pub fn new(origin: Position<f64>, start_tick: f64, duration: f64) -> Self {
         CellCompleteAnimation {
             start_time: start_tick,
-            duration: duration,
+            duration,
             time_since_start: 0.0,
-            origin: origin,
+            origin,
             finished: false,
             started: false,
         }
@@ -70,7 +69,7 @@ impl Animation for CellCompleteAnimation {
         }
 
         context
-            .set_line_dash(&JsValue::from_serde(&([] as [i32; 0])).unwrap())
+            .set_line_dash(&js_sys::Array::new())
             .unwrap();
 
         context.set_stroke_style(&colors::CELL_COMPLETE_COLOR.into());
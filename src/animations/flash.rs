@@ -3,7 +3,6 @@ use crate::geometry::Position;
 use crate::colors;
 use serde::Serialize;
 use std::fmt::Display;
-use wasm_bindgen::prelude::*;
 
 const TOGGLE_AFTER: f64 = 150.0;
 
@@ -21,10 +20,10 @@ pub struct Flash {
 impl Flash {
     pub fn new(x: f64, y: f64, start_time: f64, duration: f64) -> Self {
         Flash {
-            origin: Position { x: x, y: y },
+            origin: Position { x, y },
             last_toggle: 0.0,
-            start_time: start_time,
-            duration: duration,
+            start_time,
+            duration,
             is_finished: false,
             is_running: false,
             flash_enabled: false,
@@ -75,7 +74,7 @@ impl Animation for Flash {
         }
 
         context
-            .set_line_dash(&JsValue::from_serde(&([] as [i32; 0])).unwrap())
+            .set_line_dash(&js_sys::Array::new())
             .unwrap();
 
         context.set_stroke_style(&colors::CELL_COMPLETE_COLOR.into());
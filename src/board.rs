@@ -1,161 +1,440 @@
-use crate::animations::{Animation, Flash};
-use crate::colors;
+use crate::cell::Cell;
+use crate::game_mode::{GameMode, Outcome};
 use crate::geometry::{Position, Rect};
-use crate::pieces::{
-    LPieceLeft, LPieceRight, LinePiece, Piece, SquarePiece, TrianglePiece, ZPieceLeft, ZPieceRight,
-};
+use crate::gravity::{self, ClearGravity, Gravity};
+use crate::pieces::{Piece, PieceKind};
+use crate::puzzle::{Puzzle, PuzzleRun};
+use crate::randomizers::Randomizer;
+use crate::rotation::{self, Kick, RotationState};
+use crate::rules::Rules;
+use crate::scoring::{self, Clear, ClearType, TSpin};
 use rand::prelude::*;
 use serde::Serialize;
-use wasm_bindgen::prelude::*;
-
-const DROP_TIME: f64 = 50000f64;
-const ROW_SCORE: u32 = 1000;
+use std::collections::VecDeque;
+
+const SOFT_DROP_SCORE: u32 = 1; // per square the piece is soft dropped by
+const HARD_DROP_SCORE: u32 = 2; // per square the piece is hard dropped by
+
+/// The most upcoming pieces the board can show
+pub const MAX_PREVIEW_LENGTH: usize = 6;
+/// The most rows the board can have hidden above the visible ones
+pub const MAX_HIDDEN_ROWS: i32 = 40;
+/// How many of the hidden rows pieces spawn in, when there are that many
+const SPAWN_ROWS: i32 = 2;
+/// The most placements that can be undone in a row
+pub const MAX_UNDO: usize = 100;
+/// Mixed into the seed for the garbage generator, so that it doesn't draw the same numbers as the
+/// one pieces are dealt from
+const GARBAGE_SEED: u64 = 0x6761_7262_6167_6521;
 
 enum Rotation {
     Left,
     Right,
+    Half, // a 180, which is one rotation with its own kicks rather than two quarter turns
 }
 
+#[derive(Serialize, Copy, Clone, PartialEq)]
 enum Move {
     Left,
     Right,
 }
 
+/// A key being pressed or released, identified by its `KeyboardEvent.code`, at a game time
 #[derive(Serialize)]
-pub struct Board {
-    rows: i32,
-    cols: i32,
+enum KeyEvent {
+    Down(String, f64),
+    Up(String, f64),
+}
 
-    cells: Vec<Vec<bool>>, // indexes are [row][col]
+/// A sideways key being held down, which moves the active piece on its own
+#[derive(Serialize)]
+struct AutoShift {
+    direction: Move,
+    next_shift: f64, // the game time of the next move: DAS after the key went down, then every ARR
+}
 
-    pixels_per_cell: i32,
-    pixel_width: f64,  // width of board in pixels
-    pixel_height: f64, // height of board in pixels
+/// What the board is doing between one piece and the next
+#[derive(Serialize, Copy, Clone, Debug, PartialEq)]
+pub enum Phase {
+    Active,         // the active piece is in play
+    LineClear(f64), // cleared rows stay on the board until the given game time, then collapse
+    Entry(f64),     // the next piece is dealt at the given game time
+}
 
-    origin_x: f64, // x coord on context where the board resides
-    origin_y: f64, // y coord on context where the board resides
+/// The state of the board as a piece was about to lock, so that its placement can be undone. It
+/// has everything that decides what happens next, down to the random number generator.
+struct Snapshot {
+    cells: Vec<Vec<Option<Cell>>>,
+    active_piece: PieceKind, // spawned again when the snapshot is restored
+    queue: VecDeque<PieceKind>,
+    held_piece: Option<PieceKind>,
+    can_hold: bool,
+    score: u32,
+    lines: u32,
+    level: u32,
+    back_to_back: bool,
+    combo: i32,
+    last_clear: Option<Clear>,
+    garbage_hole: Option<i32>,
+    puzzles: Option<PuzzleRun>,
+    rng: StdRng,
+    garbage_rng: StdRng,
+    randomizer: Box<dyn Randomizer>,
+}
 
-    active_piece: Box<dyn Piece>, // the piece that the player is manipulating
+/// Board holds the rules of the game: the grid of placed cells, the active piece, scoring and the
+/// game over condition. It knows nothing about the canvas, so it can be driven and tested natively;
+/// see `BoardView` for the rendering side.
+#[derive(Serialize)]
+pub struct Board {
+    rows: i32, // the visible rows, not counting the hidden ones above them
+    cols: i32,
 
-    keys: Vec<String>, // a buffer of key presses since we last processed input
+    // indexes are [row][col], None for an empty cell; the first `Rules::hidden_rows` rows are the
+    // hidden ones above the board, which pieces are at with a negative y
+    cells: Vec<Vec<Option<Cell>>>,
 
+    active_piece: Box<dyn Piece>, // the piece that the player is manipulating
+    rotation_state: RotationState, // how far active_piece has been turned from its spawn orientation
+    last_kick: Option<Kick>,      // how the last rotation of active_piece was made to fit
+    last_move_rotated: bool,      // the last thing that moved active_piece was a rotation
+    queue: VecDeque<PieceKind>,   // the pieces that come after active_piece, in order
+    held_piece: Option<PieceKind>, // the piece put aside by the player, if any
+    can_hold: bool,               // hold can be used once per piece, until it locks
+
+    keys: Vec<KeyEvent>, // a buffer of key presses and releases since we last processed input
+    held_keys: Vec<String>, // the keys that are down, so that the OS repeating them is ignored
+    auto_shift: Option<AutoShift>, // the sideways key being held, if any
+    is_soft_dropping: bool, // the soft drop key is held down
+
+    phase: Phase, // pieces are only in play while Active
     is_paused: bool,
-    paused_rendered: bool,
     paused_at: f64, // the game time when the game was paused - used to calculate reminder of last_drop
     last_processed_tick: f64, // the last game time when we performed an update
     is_game_over: bool,
-    game_over_rendered: bool,
+    outcome: Option<Outcome>, // how the game ended, once it's over
+    elapsed: f64,             // ms of play so far, not counting pauses
     pub score: u32,
+    drop_score: u32, // the part of the score the active piece got by being dropped
+    lines: u32, // how many lines have been cleared
+    level: u32, // sets how fast pieces fall, goes up as lines are cleared
 
     last_drop: f64, // what was the game time when the active_piece was last dropped by 1 square
-
-    animations: Vec<Box<dyn Animation>>,
+    lock_started: Option<f64>, // the game time when active_piece came to rest on the stack
+    lock_resets: u32, // how many times moving active_piece restarted its lock delay
+    lowest_row: i32,  // the lowest the active_piece has been, reaching a new one allows more resets
+
+    back_to_back: bool, // the last clear was a difficult one, so the next difficult one gets a bonus
+    combo: i32,         // how many pieces in a row cleared lines, minus one; -1 when there's no combo
+    last_clear: Option<Clear>, // the most recent clear of the game
+    clears: Vec<Clear>, // clears since the view last asked
+    clearing_rows: Vec<i32>, // completed rows waiting for the line clear delay to remove them
+    chain: u32,                // the link of the chain the latest clear was, see `Clear::chain`
+
+    garbage_hole: Option<i32>, // the hole column of the last garbage row that was added
+    puzzles: Option<PuzzleRun>, // the puzzles being played, see `load_puzzles`
+    #[serde(skip)]
+    history: VecDeque<Snapshot>, // the latest placements, newest last, in modes that can undo them
+
+    seed: u64, // the seed the generators were created from, so that a game can be replayed
+    #[serde(skip)]
+    rng: StdRng, // every random decision about pieces is drawn from here
+    #[serde(skip)]
+    garbage_rng: StdRng, // the hole columns of random garbage, kept apart so pieces don't change
+    randomizer: Box<dyn Randomizer>, // decides which piece is dealt next
+    rules: Rules,
 }
 
 impl Board {
-    pub fn new(rows: i32, cols: i32, pixels_per_cell: i32, origin_x: f64, origin_y: f64) -> Self {
-        let cells: Vec<Vec<bool>> = (0..rows)
-            .map(|_| (0..cols).map(|_| false).collect())
+    /// Creates an empty board that deals its pieces with `randomizer` and plays by `rules`. Two
+    /// boards created with the same `seed`, randomizer and rules and fed the same input deal the
+    /// same pieces and end up in the same state. Neither the preview length nor garbage change
+    /// which pieces are dealt.
+    pub fn new(
+        rows: i32,
+        cols: i32,
+        seed: u64,
+        mut randomizer: Box<dyn Randomizer>,
+        mut rules: Rules,
+    ) -> Self {
+        rules.preview_length = rules.preview_length.min(MAX_PREVIEW_LENGTH);
+        rules.hidden_rows = rules.hidden_rows.clamp(0, MAX_HIDDEN_ROWS);
+        rules.start_level = rules.start_level.clamp(1, gravity::INSTANT_GRAVITY_LEVEL);
+
+        let cells: Vec<Vec<Option<Cell>>> = (0..rules.hidden_rows + rows)
+            .map(|_| (0..cols).map(|_| None).collect())
             .collect();
 
-        Board {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let first_kind = randomizer.next(&mut rng);
+        // spawned for real below, so that the first piece can block out like any other
+        let active_piece = first_kind.create(
+            Board::spawn_column(cols),
+            Board::spawn_row(rules.hidden_rows),
+        );
+        let lowest_row = active_piece.get_origin().y;
+
+        let mut board = Board {
             rows,
             cols,
-            cells: cells,
-            pixels_per_cell,
-            pixel_width: (cols * pixels_per_cell) as f64,
-            pixel_height: (rows * pixels_per_cell) as f64,
-            origin_x,
-            origin_y,
-
-            active_piece: Box::new(LinePiece::new(2, 15)),
+            cells,
+
+            active_piece,
+            rotation_state: RotationState::Spawn,
+            last_kick: None,
+            last_move_rotated: false,
+            queue: VecDeque::with_capacity(MAX_PREVIEW_LENGTH),
+            held_piece: None,
+            can_hold: true,
             keys: Vec::with_capacity(4),
+            held_keys: Vec::with_capacity(4),
+            auto_shift: None,
+            is_soft_dropping: false,
+            phase: Phase::Active,
             is_paused: Default::default(),
-            paused_rendered: false,
             paused_at: 0f64,
             last_processed_tick: 0f64,
             last_drop: 0f64,
-            animations: Vec::with_capacity(40),
+            lock_started: None,
+            lock_resets: 0,
+            lowest_row,
             score: 0,
+            drop_score: 0,
+            lines: 0,
+            level: rules.start_level,
             is_game_over: false,
-            game_over_rendered: false,
-        }
+            outcome: None,
+            elapsed: 0f64,
+            back_to_back: false,
+            combo: -1,
+            last_clear: None,
+            clears: Vec::with_capacity(4),
+            clearing_rows: Vec::with_capacity(4),
+            chain: 0,
+            garbage_hole: None,
+            puzzles: None,
+            history: VecDeque::new(),
+            seed,
+            rng,
+            garbage_rng: StdRng::seed_from_u64(seed ^ GARBAGE_SEED),
+            randomizer,
+            rules,
+        };
+
+        board.spawn_piece(first_kind);
+        board.fill_queue();
+
+        board
     }
 
-    pub fn keydown(&mut self, event: &web_sys::KeyboardEvent) {
+    /// Queues a key press, identified by its `KeyboardEvent.code`, that happened at the given game
+    /// time for the next `process_input`
+    pub fn keydown(&mut self, code: &str, time: f64) {
         if self.is_paused || self.is_game_over {
             return;
         }
 
-        self.keys.push(event.code());
+        self.keys.push(KeyEvent::Down(code.to_string(), time));
+    }
+
+    /// Queues a key release, identified by its `KeyboardEvent.code`, that happened at the given
+    /// game time for the next `process_input`
+    pub fn keyup(&mut self, code: &str, time: f64) {
+        self.keys.push(KeyEvent::Up(code.to_string(), time));
+    }
+
+    /// Lets go of every key, for when the page loses focus while keys are held and their keyups
+    /// never arrive. Key events that haven't been processed yet are dropped with them.
+    pub fn release_keys(&mut self) {
+        self.keys.clear();
+        self.held_keys.clear();
+        self.auto_shift = None;
+        self.is_soft_dropping = false;
     }
 
     pub fn process_input(&mut self) {
-        let mut cp: Vec<String> = Vec::with_capacity(4);
+        let mut cp: Vec<KeyEvent> = Vec::with_capacity(4);
         cp.append(&mut self.keys);
 
         for key in cp {
-            match key.as_ref() {
-                "ArrowUp" => self.rotate(Rotation::Left),
-                "ArrowDown" => self.rotate(Rotation::Right),
-                "ArrowLeft" => self.move_sideways(Move::Left),
-                "ArrowRight" => self.move_sideways(Move::Right),
-                "Enter" => self.place_piece(),
-                _ => (),
+            match key {
+                KeyEvent::Down(code, time) => {
+                    // a key held down is repeated by the OS, but held sideways keys are moved by
+                    // `auto_shift` and nothing else should happen more than once per press
+                    if self.held_keys.contains(&code) {
+                        continue;
+                    }
+                    self.held_keys.push(code.clone());
+
+                    match code.as_ref() {
+                        "ArrowLeft" => self.start_auto_shift(Move::Left, time),
+                        "ArrowRight" => self.start_auto_shift(Move::Right, time),
+                        "ArrowDown" => self.is_soft_dropping = true,
+                        "Backspace" => {
+                            self.undo(1);
+                        }
+                        // pieces can't be moved while there is no piece in play
+                        _ if self.phase != Phase::Active => (),
+                        "ArrowUp" | "KeyX" => self.rotate(Rotation::Right),
+                        "KeyZ" | "ControlLeft" | "ControlRight" => self.rotate(Rotation::Left),
+                        "KeyA" => self.rotate(Rotation::Half),
+                        "Space" => self.hard_drop(),
+                        "KeyC" | "ShiftLeft" | "ShiftRight" => self.hold_piece(),
+                        _ => (),
+                    }
+                }
+                KeyEvent::Up(code, time) => {
+                    self.held_keys.retain(|held| *held != code);
+
+                    match code.as_ref() {
+                        "ArrowDown" => self.is_soft_dropping = false,
+                        "ArrowLeft" | "ArrowRight" => self.stop_auto_shift(time),
+                        _ => (),
+                    }
+                }
             }
         }
     }
 
-    /// Sends the active piece to the bottom
-    fn place_piece(&mut self) {
-        let mask = self.project_piece(&self.active_piece);
-        for item in mask {
-            self.cells[item.y as usize][item.x as usize] = true;
+    /// Moves the active piece sideways as a key for it goes down, and keeps moving it for as long
+    /// as the key is held, see `auto_shift`
+    fn start_auto_shift(&mut self, direction: Move, time: f64) {
+        if self.phase == Phase::Active {
+            self.move_sideways(direction);
         }
 
-        self.new_active_piece();
+        self.auto_shift = Some(AutoShift {
+            direction,
+            next_shift: time + self.rules.das,
+        });
     }
 
-    /// Projects a piece down to the lowest point it can reach
-    fn project_piece(&self, piece: &Box<dyn Piece>) -> Vec<Position<i32>> {
-        let mut placed_piece = piece.clone();
+    /// Stops moving the active piece when the sideways key that was held goes up. If the key for
+    /// the other side is still down, that one takes over, starting from the DAS again.
+    fn stop_auto_shift(&mut self, time: f64) {
+        let still_held = if self.held_keys.iter().any(|key| key == "ArrowLeft") {
+            Some(Move::Left)
+        } else if self.held_keys.iter().any(|key| key == "ArrowRight") {
+            Some(Move::Right)
+        } else {
+            None
+        };
+
+        self.auto_shift = match (still_held, &self.auto_shift) {
+            (Some(direction), Some(held)) if direction == held.direction => return,
+            (Some(direction), _) => Some(AutoShift {
+                direction,
+                next_shift: time + self.rules.das,
+            }),
+            (None, _) => None,
+        };
+    }
+
+    /// Moves the active piece while a sideways key is held: first DAS after the key went down, then
+    /// every ARR, or straight to the wall when ARR is 0. The moves depend only on the times of the
+    /// key events and updates, not on how often the OS repeats the key.
+    fn auto_shift(&mut self, time: f64) {
+        let (direction, mut next_shift) = match &self.auto_shift {
+            Some(auto_shift) => (auto_shift.direction, auto_shift.next_shift),
+            None => return,
+        };
+
+        while time >= next_shift {
+            if self.rules.arr == 0.0 {
+                while self.move_sideways(direction) {}
+                return;
+            }
+
+            // a blocked piece tries again an ARR later, rather than catching up on missed moves
+            next_shift += self.rules.arr;
+            if !self.move_sideways(direction) {
+                next_shift = next_shift.max(time + self.rules.arr);
+            }
+        }
+
+        self.auto_shift = Some(AutoShift {
+            direction,
+            next_shift,
+        });
+    }
+
+    /// Sends the active piece to the bottom and locks it there
+    fn hard_drop(&mut self) {
+        let distance = self.drop_distance(self.active_piece.as_ref());
+        let (x, y) = self.active_piece.get_origin().into();
+        self.active_piece.set_origin(x, y + distance);
+        self.score += HARD_DROP_SCORE * distance as u32;
+        self.drop_score += HARD_DROP_SCORE * distance as u32;
+        if distance > 0 {
+            self.last_move_rotated = false;
+        }
+
+        self.lock_piece(self.last_processed_tick);
+    }
+
+    /// How many squares a piece can fall before it lands on the stack or the bottom of the board
+    fn drop_distance(&self, piece: &dyn Piece) -> i32 {
+        let mut placed_piece = piece.clone_piece();
         let (origin_x, origin_y) = placed_piece.get_origin().into();
         // todo: optimize this by projecting the mask down on the board
         // until we encounter a piece or the bottom
 
-        for y in origin_y..self.rows {
-            placed_piece.set_origin(origin_x, y);
+        let mut distance = 0;
+        while self.can_fall(placed_piece.as_ref()) {
+            distance += 1;
+            placed_piece.set_origin(origin_x, origin_y + distance);
+        }
 
-            let bb = placed_piece.bounding_box();
-            let mask = placed_piece.mask();
+        distance
+    }
 
-            if self.is_inside_board(&bb) && !self.is_colliding(&mask) {
-                continue;
-            }
+    /// Can a piece fall 1 square from where it is. A negative piece falls through empty space and
+    /// lands as soon as it reaches placed cells, which it will clear when it locks.
+    fn can_fall(&self, piece: &dyn Piece) -> bool {
+        if piece.kind().is_negative() && self.is_colliding(&piece.mask()) {
+            return false;
+        }
 
-            assert!(y - 1 >= origin_y);
+        let mut below = piece.clone_piece();
+        let (x, y) = below.get_origin().into();
+        below.set_origin(x, y + 1);
 
-            placed_piece.set_origin(origin_x, y - 1);
+        self.fits(below.as_ref())
+    }
 
-            break;
-        }
+    /// Is a piece inside the board and, unless it's a negative piece that can pass through them,
+    /// clear of the placed cells
+    fn fits(&self, piece: &dyn Piece) -> bool {
+        self.is_inside_board(&piece.bounding_box())
+            && (piece.kind().is_negative() || !self.is_colliding(&piece.mask()))
+    }
 
-        return placed_piece.mask();
+    /// Projects a piece down to the lowest point it can reach
+    fn project_piece(&self, piece: &dyn Piece) -> Vec<Position<i32>> {
+        let mut placed_piece = piece.clone_piece();
+        let (origin_x, origin_y) = placed_piece.get_origin().into();
+        placed_piece.set_origin(origin_x, origin_y + self.drop_distance(piece));
+
+        placed_piece.mask()
     }
 
-    /// Is the bounding box bb of a piece inside the board
+    /// Is the bounding box bb of a piece inside the board, counting the hidden rows
     fn is_inside_board(&self, bb: &Rect<i32>) -> bool {
-        if bb.x1 < 0 || bb.x2 >= self.cols || bb.y1 < 0 || bb.y2 >= self.rows {
-            return false;
-        }
+        !(bb.x1 < 0
+            || bb.x2 >= self.cols
+            || bb.y1 < -self.rules.hidden_rows
+            || bb.y2 >= self.rows)
+    }
 
-        return true;
+    /// Where row y of the board is in `cells`, y being negative for the hidden rows
+    fn row_index(&self, y: i32) -> usize {
+        (y + self.rules.hidden_rows) as usize
     }
 
     /// Is the mask of a piece colding with any existing pieces on the board
-    fn is_colliding(&self, mask: &Vec<Position<i32>>) -> bool {
+    fn is_colliding(&self, mask: &[Position<i32>]) -> bool {
         for item in mask {
-            if self.cells[item.y as usize][item.x as usize] {
+            if self.cells[self.row_index(item.y)][item.x as usize].is_some() {
                 return true;
             }
         }
@@ -163,42 +442,54 @@ impl Board {
         false
     }
 
+    /// Rotates the active piece following the Super Rotation System: the rotated piece is tried at
+    /// each offset of the kick table for its kind and rotation, and the first one that fits is used.
+    /// If none of them fit the piece doesn't rotate.
     fn rotate(&mut self, rotation: Rotation) {
-        let mut attempt = self.active_piece.clone();
-
-        match rotation {
-            Rotation::Left => attempt.rotate_left(),
-            Rotation::Right => attempt.rotate_right(),
-        }
-
-        let mut bb = attempt.bounding_box();
+        let mut rotated = self.active_piece.clone();
 
-        if !self.is_inside_board(&bb) {
-            while bb.x2 >= self.cols {
-                attempt.move_left();
-                bb = attempt.bounding_box();
+        let next_state = match rotation {
+            Rotation::Left => {
+                rotated.rotate_left();
+                self.rotation_state.rotated_left()
             }
-
-            while bb.x1 < 0 {
-                attempt.move_right();
-                bb = attempt.bounding_box();
+            Rotation::Right => {
+                rotated.rotate_right();
+                self.rotation_state.rotated_right()
+            }
+            Rotation::Half => {
+                rotated.rotate_180();
+                self.rotation_state.rotated_180()
             }
+        };
 
-            if !self.is_inside_board(&bb) {
-                return;
+        let (x, y) = rotated.get_origin().into();
+        let kicks = rotation::kicks(self.active_piece.kind(), self.rotation_state, next_state);
+
+        for (test, (kick_x, kick_y)) in kicks.into_iter().enumerate() {
+            let mut attempt = rotated.clone();
+            attempt.set_origin(x + kick_x, y + kick_y);
+
+            if !self.fits(attempt.as_ref()) {
+                continue;
             }
-        }
 
-        let mask = attempt.mask();
+            self.active_piece = attempt;
+            self.reset_lock_delay();
+            self.rotation_state = next_state;
+            self.last_move_rotated = true;
+            self.last_kick = Some(Kick {
+                test,
+                x: kick_x,
+                y: kick_y,
+                half_turn: matches!(rotation, Rotation::Half),
+            });
 
-        if self.is_colliding(&mask) {
             return;
         }
-
-        self.active_piece = attempt;
     }
 
-    fn move_sideways(&mut self, direction: Move) {
+    fn move_sideways(&mut self, direction: Move) -> bool {
         let mut attempt = self.active_piece.clone();
 
         match direction {
@@ -206,256 +497,983 @@ impl Board {
             Move::Right => attempt.move_right(),
         }
 
-        let bb = attempt.bounding_box();
-        if !self.is_inside_board(&bb) {
-            return;
+        if !self.fits(attempt.as_ref()) {
+            return false;
         }
 
-        let mask = attempt.mask();
-        if self.is_colliding(&mask) {
+        self.active_piece = attempt;
+        self.last_move_rotated = false;
+        self.reset_lock_delay();
+
+        true
+    }
+
+    /// Moving or rotating a piece that rests on the stack restarts its lock delay, but only so many
+    /// times: after that the piece locks as soon as it's on the stack
+    fn reset_lock_delay(&mut self) {
+        if self.lock_started.is_none() {
             return;
         }
 
-        self.active_piece = attempt;
+        self.lock_resets += 1;
+        if self.lock_resets <= self.rules.lock_reset_limit {
+            self.lock_started = Some(self.last_processed_tick);
+        }
     }
 
     pub fn update(&mut self, time: f64) {
-        self.last_processed_tick = time;
         if self.is_paused || self.is_game_over {
+            self.last_processed_tick = time;
+            return;
+        }
+
+        if self.last_processed_tick > 0f64 {
+            self.elapsed += time - self.last_processed_tick;
+        }
+        self.last_processed_tick = time;
+
+        if self.rules.mode.is_complete(self.level, self.lines, self.elapsed) {
+            self.end_game(Outcome::Completed);
             return;
         }
 
+        // let the delays that have run out end, possibly more than one in the same update
+        loop {
+            match self.phase {
+                Phase::Active => break,
+                Phase::LineClear(until) if time >= until => self.collapse_rows(until),
+                Phase::Entry(until) if time >= until => {
+                    self.phase = Phase::Active;
+                    self.new_active_piece();
+                }
+                _ => {
+                    // a held sideways key keeps charging while there is no piece in play, but the
+                    // moves it would have made aren't saved up for the next piece
+                    if let Some(auto_shift) = self.auto_shift.as_mut() {
+                        auto_shift.next_shift = auto_shift.next_shift.max(time);
+                    }
+                    return;
+                }
+            }
+
+            if self.is_game_over {
+                return;
+            }
+        }
+
+        self.auto_shift(time);
+
         if self.last_drop == 0f64 {
             self.last_drop = time;
-        } else if time - self.last_drop > DROP_TIME {
-            self.try_drop();
-            self.last_drop = time;
+        } else {
+            self.apply_gravity(time);
         }
 
-        // check for completed rows
-        let mut complete_rows: Vec<usize> = Vec::with_capacity(20);
-        for y in (0..(self.rows as usize)).rev() {
-            let is_complete = self.cells[y].iter().all(|val| *val);
-            if is_complete {
-                complete_rows.push(y);
-                self.score += ROW_SCORE;
+        self.update_lock(time);
+    }
+
+    /// Scores the completed rows, together with the T-spin the piece was locked with, as one clear
+    /// and leaves them for `collapse_rows` to remove
+    fn clear_lines(&mut self, t_spin: Option<TSpin>) {
+        let complete_rows = self.complete_rows();
+
+        let clear_type = match ClearType::new(complete_rows.len(), t_spin) {
+            Some(clear_type) => clear_type,
+            None => {
+                self.combo = -1;
+                return;
             }
+        };
+
+        // a T-spin that clears no lines breaks the combo but not the back-to-back
+        let mut back_to_back = false;
+        if complete_rows.is_empty() {
+            self.combo = -1;
+        } else {
+            self.combo += 1;
+            back_to_back = self.back_to_back && clear_type.is_difficult();
+            self.back_to_back = clear_type.is_difficult();
+        }
+
+        let combo = self.combo.max(0) as u32;
+        let score = scoring::clear_score(clear_type, self.level, back_to_back, combo);
+        self.score += score;
+        self.add_lines(complete_rows.len());
+
+        self.chain = 0;
+        self.clearing_rows = complete_rows.clone();
+        let clear = Clear {
+            clear_type,
+            rows: complete_rows,
+            back_to_back,
+            combo,
+            chain: 0,
+            score,
+        };
+        self.record_clear(clear);
+
+        if self.rules.mode.is_complete(self.level, self.lines, self.elapsed) {
+            self.end_game(Outcome::Completed);
         }
+    }
+
+    /// Makes a clear the latest one, for the view and towards the goal of the puzzle being played
+    fn record_clear(&mut self, clear: Clear) {
+        if let Some(run) = self.puzzles.as_mut() {
+            run.lines += clear.rows.len() as u32;
+            run.clear_types.push(clear.clear_type);
+        }
+
+        self.last_clear = Some(clear.clone());
+        self.clears.push(clear);
+    }
 
-        for to_remove in complete_rows {
-            for i in 0..10 {
-                self.animations.push(Box::new(Flash::new(
-                    self.origin_x + i as f64 * self.pixels_per_cell as f64,
-                    self.origin_y + to_remove as f64 * self.pixels_per_cell as f64,
-                    time,
-                    1500.0,
-                )));
+    /// The rows that have no empty cells, top to bottom, hidden ones included
+    fn complete_rows(&self) -> Vec<i32> {
+        (-self.rules.hidden_rows..self.rows)
+            .filter(|y| self.cells[self.row_index(*y)].iter().all(Option::is_some))
+            .collect()
+    }
+
+    /// Takes the given rows out of the board. With naive gravity the rows above them move down
+    /// in their place; with cascade gravity the rows are only emptied, for `settle_clusters` to
+    /// let the cells above them fall.
+    fn remove_rows(&mut self, rows: &[i32]) {
+        // rows are top to bottom, so removing a row and inserting an empty one at the top leaves
+        // the indexes of the rows below it untouched
+        for y in rows.iter() {
+            let index = self.row_index(*y);
+            match self.rules.clear_gravity {
+                ClearGravity::Naive => {
+                    self.cells.remove(index);
+                    self.cells.insert(0, (0..self.cols).map(|_| None).collect());
+                }
+                ClearGravity::Cascade => {
+                    self.cells[index].iter_mut().for_each(|cell| *cell = None);
+                }
             }
-            self.cells.remove(to_remove);
-            self.cells
-                .insert(0, (0..self.cols).map(|_| false).collect());
         }
+    }
 
-        for animation in self.animations.iter_mut() {
-            animation.update(time);
+    /// Removes the rows being cleared, at the given game time, and moves on to dealing the next
+    /// piece. With cascade gravity the cells left behind then fall, and the rows they complete are
+    /// cleared in turn as the next link of the chain started by the piece that locked.
+    fn collapse_rows(&mut self, time: f64) {
+        while !self.clearing_rows.is_empty() {
+            let rows = std::mem::take(&mut self.clearing_rows);
+            self.remove_rows(&rows);
+
+            if self.rules.clear_gravity == ClearGravity::Cascade {
+                self.settle_clusters();
+                self.clear_chain();
+            }
+
+            if self.is_game_over {
+                return;
+            }
+            if !self.clearing_rows.is_empty() && self.rules.line_clear_delay > 0.0 {
+                self.phase = Phase::LineClear(time + self.rules.line_clear_delay);
+                return;
+            }
+        }
+
+        self.check_puzzle();
+        if self.is_game_over {
+            return;
+        }
+
+        if self.rules.entry_delay > 0.0 {
+            self.phase = Phase::Entry(time + self.rules.entry_delay);
+        } else {
+            self.phase = Phase::Active;
+            self.new_active_piece();
         }
     }
 
-    fn try_drop(&mut self) {
-        let (x, y) = self.active_piece.get_origin().into();
+    /// Scores the rows completed by cells that cascaded into place as the next link of the chain,
+    /// without T-spin, back-to-back or combo bonuses, and leaves them for `collapse_rows` to remove
+    fn clear_chain(&mut self) {
+        let complete_rows = self.complete_rows();
+        let clear_type = match ClearType::new(complete_rows.len(), None) {
+            Some(clear_type) => clear_type,
+            None => return,
+        };
+
+        self.chain += 1;
+        let score = scoring::chain_score(clear_type, self.level, self.chain);
+        self.score += score;
+        self.add_lines(complete_rows.len());
+
+        self.clearing_rows = complete_rows.clone();
+        let clear = Clear {
+            clear_type,
+            rows: complete_rows,
+            back_to_back: false,
+            combo: self.combo.max(0) as u32,
+            chain: self.chain,
+            score,
+        };
+        self.record_clear(clear);
+
+        if self.rules.mode.is_complete(self.level, self.lines, self.elapsed) {
+            self.end_game(Outcome::Completed);
+        }
+    }
 
+    /// Lets every cluster of filled cells that share a side fall as far as it can, as if it were a
+    /// piece of its own. Clusters fall a row at a time, lowest first, so that a cluster resting on
+    /// another one follows it down.
+    fn settle_clusters(&mut self) {
+        loop {
+            let mut clusters = self.clusters();
+            clusters.sort_by_key(|cluster| {
+                std::cmp::Reverse(cluster.iter().map(|(_, y)| *y).max().unwrap())
+            });
+
+            let mut has_fallen = false;
+            for cluster in clusters.iter() {
+                let can_fall = cluster.iter().all(|(x, y)| {
+                    *y + 1 < self.cells.len()
+                        && (self.cells[*y + 1][*x].is_none() || cluster.contains(&(*x, *y + 1)))
+                });
+                if !can_fall {
+                    continue;
+                }
+
+                let cells: Vec<Option<Cell>> = cluster
+                    .iter()
+                    .map(|(x, y)| self.cells[*y][*x].take())
+                    .collect();
+                for ((x, y), cell) in cluster.iter().zip(cells) {
+                    self.cells[*y + 1][*x] = cell;
+                }
+                has_fallen = true;
+            }
+
+            if !has_fallen {
+                return;
+            }
+        }
+    }
+
+    /// Groups the filled cells of the board, as (x, y) indexes into `cells`, into clusters of
+    /// cells that are connected through shared sides
+    fn clusters(&self) -> Vec<Vec<(usize, usize)>> {
+        let (rows, cols) = (self.cells.len(), self.cols as usize);
+        let mut seen = vec![vec![false; cols]; rows];
+        let mut clusters = Vec::new();
+
+        for y in 0..rows {
+            for x in 0..cols {
+                if seen[y][x] || self.cells[y][x].is_none() {
+                    continue;
+                }
+
+                seen[y][x] = true;
+                let mut cluster = vec![(x, y)];
+                let mut i = 0;
+                while i < cluster.len() {
+                    let (x, y) = cluster[i];
+                    let neighbours = [
+                        (x.wrapping_sub(1), y),
+                        (x + 1, y),
+                        (x, y.wrapping_sub(1)),
+                        (x, y + 1),
+                    ];
+                    for (nx, ny) in neighbours.iter().copied() {
+                        if nx < cols && ny < rows && !seen[ny][nx] && self.cells[ny][nx].is_some() {
+                            seen[ny][nx] = true;
+                            cluster.push((nx, ny));
+                        }
+                    }
+                    i += 1;
+                }
+
+                clusters.push(cluster);
+            }
+        }
+
+        clusters
+    }
+
+    /// Counts cleared lines towards the level
+    fn add_lines(&mut self, lines: usize) {
+        self.lines += lines as u32;
+        if let Some(levels) = self.lines.checked_div(self.rules.lines_per_level) {
+            self.level = self.level.max(self.rules.start_level + levels);
+        }
+    }
+
+    /// Drops the active piece by as many squares as the gravity of the current level allows since
+    /// the last drop
+    fn apply_gravity(&mut self, time: f64) {
+        match gravity::gravity(self.level) {
+            Gravity::Instant => {
+                while !self.is_grounded() {
+                    self.try_drop();
+                }
+                self.last_drop = time;
+            }
+            Gravity::Interval(mut interval) => {
+                if self.is_soft_dropping {
+                    interval /= self.rules.soft_drop_factor;
+                }
+
+                // never fall more than the height of the board, e.g. after a long pause
+                let mut drops = 0;
+                while time - self.last_drop > interval && drops < self.rows {
+                    if self.try_drop() && self.is_soft_dropping {
+                        self.score += SOFT_DROP_SCORE;
+                        self.drop_score += SOFT_DROP_SCORE;
+                    }
+                    self.last_drop += interval;
+                    drops += 1;
+                }
+
+                if drops == self.rows {
+                    self.last_drop = time;
+                }
+            }
+        }
+    }
+
+    /// Moves the active piece down by 1 square if there is room for it and tells whether it did. A
+    /// piece that can't fall is left for `update_lock` to lock.
+    fn try_drop(&mut self) -> bool {
+        if self.is_grounded() {
+            return false;
+        }
+
+        let (x, y) = self.active_piece.get_origin().into();
         self.active_piece.set_origin(x, y + 1);
+        self.last_move_rotated = false;
 
-        let bb = self.active_piece.bounding_box();
-        let mask = self.active_piece.mask();
-        if self.is_inside_board(&bb) && !self.is_colliding(&mask) {
+        if y + 1 > self.lowest_row {
+            self.lowest_row = y + 1;
+            self.lock_resets = 0;
+        }
+
+        true
+    }
+
+    /// Is the active piece resting on the stack or the bottom of the board
+    fn is_grounded(&self) -> bool {
+        !self.can_fall(self.active_piece.as_ref())
+    }
+
+    /// Starts the lock delay when the active piece comes to rest and locks it when the delay runs
+    /// out or the piece used up its resets
+    fn update_lock(&mut self, time: f64) {
+        if !self.is_grounded() {
+            self.lock_started = None;
             return;
         }
 
-        // undo piece drop and place piece
-        self.active_piece.set_origin(x, y);
+        let lock_started = *self.lock_started.get_or_insert(time);
+        if time - lock_started >= self.rules.lock_delay
+            || self.lock_resets > self.rules.lock_reset_limit
+        {
+            self.lock_piece(time);
+        }
+    }
+
+    /// Writes the active piece into the board where it is, at the given game time, clears the
+    /// lines it completed and deals the next one, after the line clear and entry delays. A
+    /// negative piece empties the cells under it instead.
+    fn lock_piece(&mut self, time: f64) {
+        if self.rules.mode.can_undo() {
+            self.take_snapshot();
+        }
+
+        let t_spin = self.t_spin();
+
+        let kind = self.active_piece.kind();
+        let cell = if kind.is_negative() {
+            None
+        } else {
+            Some(Cell::new(kind))
+        };
         let mask = self.active_piece.mask();
-        for item in mask {
-            self.cells[item.y as usize][item.x as usize] = true;
+        for item in mask.iter() {
+            let y = self.row_index(item.y);
+            self.cells[y][item.x as usize] = cell;
         }
 
-        self.new_active_piece();
+        // a piece that locks above the visible rows tops out, as does one that locks partly above
+        // them if partial lock out is on
+        let hidden = mask.iter().filter(|item| item.y < 0).count();
+        let is_locked_out = (self.rules.lock_out && hidden == mask.len())
+            || (self.rules.partial_lock_out && hidden > 0);
+        if !kind.is_negative() && is_locked_out {
+            self.top_out();
+            // a puzzle that tops out has already moved on to the next one
+            if self.is_game_over || self.puzzles.is_some() {
+                return;
+            }
+        }
+
+        self.clear_lines(t_spin);
+        if self.is_game_over {
+            return;
+        }
+
+        if !self.clearing_rows.is_empty() && self.rules.line_clear_delay > 0.0 {
+            self.phase = Phase::LineClear(time + self.rules.line_clear_delay);
+        } else {
+            self.collapse_rows(time);
+        }
     }
 
-    fn new_active_piece(&mut self) {
-        let mut rng = thread_rng();
-        let next = rng.gen_range(0, 350);
-        let next_active_piece: Box<dyn Piece>;
-        if next > 300 {
-            next_active_piece = Box::new(TrianglePiece::new(self.cols / 2, 1));
-        } else if next > 250 {
-            next_active_piece = Box::new(ZPieceRight::new(self.cols / 2, 1));
-        } else if next > 200 {
-            next_active_piece = Box::new(ZPieceLeft::new(self.cols / 2, 1));
-        } else if next > 150 {
-            next_active_piece = Box::new(LinePiece::new(self.cols / 2, 1));
-        } else if next > 100 {
-            next_active_piece = Box::new(SquarePiece::new(self.cols / 2, 1));
-        } else if next > 50 {
-            next_active_piece = Box::new(LPieceLeft::new(self.cols / 2, 1));
+    /// Remembers the board as it is before the active piece locks, without the points it got for
+    /// being dropped, dropping the oldest snapshot once there are `MAX_UNDO` of them
+    fn take_snapshot(&mut self) {
+        if self.history.len() == MAX_UNDO {
+            self.history.pop_front();
+        }
+
+        self.history.push_back(Snapshot {
+            cells: self.cells.clone(),
+            active_piece: self.active_piece.kind(),
+            queue: self.queue.clone(),
+            held_piece: self.held_piece,
+            can_hold: self.can_hold,
+            score: self.score - self.drop_score,
+            lines: self.lines,
+            level: self.level,
+            back_to_back: self.back_to_back,
+            combo: self.combo,
+            last_clear: self.last_clear.clone(),
+            garbage_hole: self.garbage_hole,
+            puzzles: self.puzzles.clone(),
+            rng: self.rng.clone(),
+            garbage_rng: self.garbage_rng.clone(),
+            randomizer: self.randomizer.clone(),
+        });
+    }
+
+    /// Takes back the last `placements` pieces that locked, or as many as there are snapshots of,
+    /// in modes that allow it. The board goes back to how it was before the earliest of them
+    /// locked, with that piece back at the top. Returns how many placements were undone.
+    pub fn undo(&mut self, placements: usize) -> usize {
+        if !self.rules.mode.can_undo() || self.is_game_over {
+            return 0;
+        }
+
+        let undone = placements.min(self.history.len());
+        let earliest = self.history.len() - undone;
+        let snapshot = match self.history.drain(earliest..).next() {
+            Some(snapshot) => snapshot,
+            None => return 0,
+        };
+
+        self.cells = snapshot.cells;
+        self.queue = snapshot.queue;
+        self.held_piece = snapshot.held_piece;
+        self.score = snapshot.score;
+        self.lines = snapshot.lines;
+        self.level = snapshot.level;
+        self.back_to_back = snapshot.back_to_back;
+        self.combo = snapshot.combo;
+        self.last_clear = snapshot.last_clear;
+        self.garbage_hole = snapshot.garbage_hole;
+        self.puzzles = snapshot.puzzles;
+        self.rng = snapshot.rng;
+        self.garbage_rng = snapshot.garbage_rng;
+        self.randomizer = snapshot.randomizer;
+
+        self.clearing_rows.clear();
+        self.chain = 0;
+        self.phase = Phase::Active;
+        self.spawn_piece(snapshot.active_piece);
+        self.can_hold = snapshot.can_hold;
+
+        undone
+    }
+
+    /// Tells whether the active piece is locking with a T-spin, using the three-corner rule: the
+    /// piece is a triangle whose last move was a rotation, and at least three of the four cells
+    /// diagonal to its origin are filled. It's a full T-spin if both corners on the side the
+    /// triangle points to are filled, or if the rotation needed the last kick of the table, and a
+    /// mini otherwise.
+    fn t_spin(&self) -> Option<TSpin> {
+        if self.active_piece.kind() != PieceKind::Triangle || !self.last_move_rotated {
+            return None;
+        }
+
+        let (x, y) = self.active_piece.get_origin().into();
+        let corners = [(-1, -1), (1, -1), (-1, 1), (1, 1)];
+        let filled = corners
+            .iter()
+            .filter(|(dx, dy)| self.is_filled(x + dx, y + dy))
+            .count();
+        if filled < 3 {
+            return None;
+        }
+
+        // the side the triangle points to, which turns with the piece from pointing up at spawn
+        let (front_x, front_y) = match self.rotation_state {
+            RotationState::Spawn => (0, -1),
+            RotationState::Right => (1, 0),
+            RotationState::Reverse => (0, 1),
+            RotationState::Left => (-1, 0),
+        };
+        let front_filled = corners
+            .iter()
+            .filter(|(dx, dy)| *dx == front_x || *dy == front_y)
+            .filter(|(dx, dy)| self.is_filled(x + dx, y + dy))
+            .count();
+
+        // the fifth and last test of the quarter turn kick table only fits a triangle into a very
+        // tight spot; the 180 table has more tests, and its fifth is an ordinary sideways kick
+        let last_test = self
+            .last_kick
+            .is_some_and(|kick| !kick.half_turn && kick.test == 4);
+        if front_filled == 2 || last_test {
+            Some(TSpin::Full)
         } else {
-            next_active_piece = Box::new(LPieceRight::new(self.cols / 2, 1));
+            Some(TSpin::Mini)
+        }
+    }
+
+    /// Is the cell at (x, y) taken, counting everything outside the board as taken
+    fn is_filled(&self, x: i32, y: i32) -> bool {
+        if x < 0 || x >= self.cols || y < -self.rules.hidden_rows || y >= self.rows {
+            return true;
         }
 
+        self.cells[self.row_index(y)][x as usize].is_some()
+    }
+
+    /// Puts the active piece aside and brings back the one that was held before, or the next piece
+    /// if nothing was held yet
+    fn hold_piece(&mut self) {
+        if !self.can_hold {
+            return;
+        }
+
+        // can_hold is cleared first, so that a puzzle that tops out as the piece spawns leaves
+        // hold free for the first piece of the next one
+        self.can_hold = false;
+        let next = match self.held_piece.replace(self.active_piece.kind()) {
+            Some(held) => held,
+            None => self.next_piece_kind(),
+        };
+        self.spawn_piece(next);
+    }
+
+    fn new_active_piece(&mut self) {
+        let next = self.next_piece_kind();
+        self.can_hold = true;
+        self.spawn_piece(next);
+    }
+
+    /// The row pieces spawn at: as far up the hidden rows as `SPAWN_ROWS`, or row 1 when there
+    /// are no hidden rows
+    fn spawn_row(hidden_rows: i32) -> i32 {
+        1 - hidden_rows.min(SPAWN_ROWS)
+    }
+
+    /// The column pieces spawn at: left of the middle on boards with an even number of columns,
+    /// as in the guideline
+    fn spawn_column(cols: i32) -> i32 {
+        (cols - 1) / 2
+    }
+
+    /// Makes a piece of the given kind the active piece, at the spawn position and orientation. A
+    /// piece that doesn't fit there blocks out, unless block out is off: then it's moved up the
+    /// hidden rows until it fits.
+    fn spawn_piece(&mut self, kind: PieceKind) {
+        let spawn_row = Board::spawn_row(self.rules.hidden_rows);
+        let mut next_active_piece = kind.create(Board::spawn_column(self.cols), spawn_row);
+        if !self.rules.block_out {
+            while !self.fits(next_active_piece.as_ref())
+                && next_active_piece.bounding_box().y1 > -self.rules.hidden_rows
+            {
+                let (x, y) = next_active_piece.get_origin().into();
+                next_active_piece.set_origin(x, y - 1);
+            }
+        }
+
+        self.rotation_state = RotationState::Spawn;
+        self.last_kick = None;
+        self.last_move_rotated = false;
+
         self.last_drop = 0f64;
+        self.lock_started = None;
+        self.lock_resets = 0;
+        self.lowest_row = next_active_piece.get_origin().y;
+        self.drop_score = 0;
+
+        if !self.fits(next_active_piece.as_ref()) {
+            self.top_out();
+            // a puzzle that tops out has already dealt the first piece of the next one
+            if self.puzzles.is_some() {
+                return;
+            }
+        }
 
-        let mask = next_active_piece.mask();
-        if self.is_colliding(&mask) {
-            self.is_game_over = true;
-        } else {
+        if !self.is_game_over {
             self.active_piece = next_active_piece;
         }
     }
 
-    pub fn draw(&mut self, context: &web_sys::CanvasRenderingContext2d) {
-        // draw border
-        context.set_stroke_style(&colors::BORDER.into());
-        context.set_line_width(1.0);
-        context
-            .set_line_dash(&JsValue::from_serde(&([] as [i32; 0])).unwrap())
-            .unwrap();
-        context.begin_path();
-        context.move_to(self.relative_x(0.0), self.relative_y(0.0));
-        context.line_to(self.relative_x(self.pixel_width), self.relative_y(0.0));
-        context.line_to(
-            self.relative_x(self.pixel_width),
-            self.relative_y(self.pixel_height),
-        );
-        context.line_to(self.relative_x(0.0), self.relative_y(self.pixel_height));
-        context.line_to(self.relative_x(0.0), self.relative_y(0.0));
-
-        context.stroke();
-
-        // draw cross hatch
-        context.begin_path();
-        context
-            .set_line_dash(&JsValue::from_serde(&([] as [i32; 0])).unwrap())
-            .unwrap();
-
-        for i in 1..self.cols {
-            context.move_to(
-                self.relative_x((i * self.pixels_per_cell).into()),
-                self.origin_y,
-            );
-
-            context.line_to(
-                self.relative_x((i * self.pixels_per_cell).into()),
-                self.relative_y(self.pixel_height),
-            );
-        }
-
-        for j in 1..self.rows {
-            context.move_to(
-                self.origin_x,
-                self.relative_y((j * self.pixels_per_cell).into()),
-            );
-
-            context.line_to(
-                self.relative_x(self.pixel_width),
-                self.relative_y((j * self.pixels_per_cell).into()),
-            );
-        }
-
-        context.stroke();
-
-        // draw pieces on the board
-        context.set_fill_style(&"blue".into());
-
-        for (y, row) in self.cells.iter().enumerate() {
-            for (x, val) in row.iter().enumerate() {
-                if *val {
-                    context.fill_rect(
-                        self.relative_x((x * self.pixels_per_cell as usize) as f64),
-                        self.relative_y((y * self.pixels_per_cell as usize) as f64),
-                        self.pixels_per_cell as f64,
-                        self.pixels_per_cell as f64,
-                    );
+    /// The stack reached the top of the board: the game is over, unless the mode empties the board
+    /// and carries on. A puzzle that tops out fails there and then, and the first piece of the next
+    /// one is dealt.
+    fn top_out(&mut self) {
+        if self.puzzles.is_some() {
+            self.finish_puzzle(false);
+            if !self.is_game_over {
+                self.phase = Phase::Active;
+                self.new_active_piece();
+            }
+            return;
+        }
+        if self.rules.mode.can_top_out() {
+            self.end_game(Outcome::ToppedOut);
+            return;
+        }
+
+        for row in self.cells.iter_mut() {
+            for cell in row.iter_mut() {
+                *cell = None;
+            }
+        }
+    }
+
+    fn end_game(&mut self, outcome: Outcome) {
+        self.is_game_over = true;
+        self.outcome = Some(outcome);
+    }
+
+    /// Takes the next piece off the queue and tops the queue back up to the preview length
+    fn next_piece_kind(&mut self) -> PieceKind {
+        let next = match self.queue.pop_front() {
+            Some(kind) => kind,
+            // a puzzle deals only its own pieces, so once they're all out the held one is next
+            None if self.puzzles.is_some() && self.held_piece.is_some() => {
+                self.held_piece.take().unwrap()
+            }
+            None => self.randomizer.next(&mut self.rng),
+        };
+
+        self.fill_queue();
+
+        next
+    }
+
+    fn fill_queue(&mut self) {
+        if self.puzzles.is_some() {
+            return;
+        }
+
+        while self.queue.len() < self.rules.preview_length {
+            let kind = self.randomizer.next(&mut self.rng);
+            self.queue.push_back(kind);
+        }
+    }
+
+    /// Plays the given puzzles one after another, in place of the game so far: the score starts
+    /// over and the mode becomes `GameMode::Puzzle`. Returns a message saying what's wrong if a
+    /// puzzle doesn't fit on the board.
+    pub fn load_puzzles(&mut self, puzzles: Vec<Puzzle>) -> Result<(), String> {
+        if puzzles.is_empty() {
+            return Err("there are no puzzles".to_string());
+        }
+        for (i, puzzle) in puzzles.iter().enumerate() {
+            if !puzzle.board.is_empty() && puzzle.width() != self.cols as usize {
+                return Err(format!(
+                    "puzzle {}: the board is {} columns wide, not {}",
+                    i,
+                    puzzle.width(),
+                    self.cols
+                ));
+            }
+            if puzzle.board.len() as i32 > self.rows {
+                return Err(format!(
+                    "puzzle {}: the board has {} rows, more than the {} there are",
+                    i,
+                    puzzle.board.len(),
+                    self.rows
+                ));
+            }
+        }
+
+        self.rules.mode = GameMode::Puzzle;
+        self.puzzles = Some(PuzzleRun::new(puzzles));
+        self.history.clear();
+        self.start_puzzle();
+
+        self.score = 0;
+        self.lines = 0;
+        self.level = self.rules.start_level;
+        self.elapsed = 0f64;
+        self.is_game_over = false;
+        self.outcome = None;
+        self.last_clear = None;
+        self.phase = Phase::Active;
+        self.new_active_piece();
+
+        Ok(())
+    }
+
+    /// Sets the board up for the current puzzle: its cells at the bottom and its pieces in the
+    /// queue, with nothing held
+    fn start_puzzle(&mut self) {
+        let puzzle = match self.puzzles.as_mut() {
+            Some(run) => {
+                run.reset();
+                run.puzzle().clone()
+            }
+            None => return,
+        };
+
+        for row in self.cells.iter_mut() {
+            for cell in row.iter_mut() {
+                *cell = None;
+            }
+        }
+        for (i, row) in puzzle.board.iter().rev().enumerate() {
+            let index = self.row_index(self.rows - 1 - i as i32);
+            for (x, filled) in row.iter().enumerate() {
+                if *filled {
+                    self.cells[index][x] = Some(Cell::garbage());
                 }
             }
         }
 
-        // draw active piece
-        if !self.is_game_over {
-            let origin = self.active_piece.get_origin();
-            self.active_piece.draw(
-                context,
-                self.origin_x + (origin.x * self.pixels_per_cell) as f64,
-                self.origin_y + (origin.y * self.pixels_per_cell) as f64,
-                self.pixels_per_cell as f64,
-            );
+        self.queue = puzzle.pieces.into_iter().collect();
+        self.held_piece = None;
+        self.clearing_rows.clear();
+        self.back_to_back = false;
+        self.combo = -1;
+    }
+
+    /// Moves on to the next puzzle once the current one is over after a piece locked: solved by
+    /// reaching its goal, or failed by running out of pieces
+    fn check_puzzle(&mut self) {
+        let is_board_empty = self.cells.iter().flatten().all(Option::is_none);
+        let is_out_of_pieces = self.queue.is_empty() && self.held_piece.is_none();
+        let solved = match self.puzzles.as_ref() {
+            Some(run) => run.is_goal_met(is_board_empty),
+            None => return,
+        };
+
+        if solved || is_out_of_pieces {
+            self.finish_puzzle(solved);
         }
+    }
 
-        // draw the projection
-        if !self.is_game_over {
-            context.set_stroke_style(&colors::PROJECTION_STROKE.into());
-            context
-                .set_line_dash(&JsValue::from_serde(&vec![3, 3]).unwrap())
-                .unwrap();
-            context.begin_path();
-
-            let mask = self.project_piece(&self.active_piece);
-
-            for item in mask {
-                context.move_to(
-                    self.origin_x + (item.x * self.pixels_per_cell) as f64,
-                    self.origin_y + (item.y * self.pixels_per_cell) as f64,
-                );
-                context.line_to(
-                    self.origin_x + ((item.x + 1) * self.pixels_per_cell) as f64,
-                    self.origin_y + (item.y * self.pixels_per_cell) as f64,
-                );
-                context.line_to(
-                    self.origin_x + ((item.x + 1) * self.pixels_per_cell) as f64,
-                    self.origin_y + ((item.y + 1) * self.pixels_per_cell) as f64,
-                );
-                context.line_to(
-                    self.origin_x + (item.x * self.pixels_per_cell) as f64,
-                    self.origin_y + ((item.y + 1) * self.pixels_per_cell) as f64,
-                );
-                context.line_to(
-                    self.origin_x + (item.x * self.pixels_per_cell) as f64,
-                    self.origin_y + (item.y * self.pixels_per_cell) as f64,
-                );
-            }
-
-            context.stroke();
-        }
-
-        // draw animations
-        for animation in &self.animations {
-            animation.draw(context, 0.0, 0.0, self.pixels_per_cell as f64);
-        }
-
-        if self.is_paused {
-            self.paused_rendered = true;
-            web_sys::console::log_1(&"drawing pause".into());
-            context.set_fill_style(&"white".into());
-            context.set_font("24px sans-serif");
-            context
-                .fill_text("Paused", self.relative_x(10.0), self.relative_y(10.0))
-                .unwrap();
+    /// Records whether the current puzzle was solved and sets up the next one. The game is complete
+    /// after the last one.
+    fn finish_puzzle(&mut self, solved: bool) {
+        let run = match self.puzzles.as_mut() {
+            Some(run) => run,
+            None => return,
+        };
+
+        run.solved.push(solved);
+        if run.current + 1 == run.puzzles.len() {
+            self.end_game(Outcome::Completed);
+            return;
+        }
+
+        run.current += 1;
+        self.start_puzzle();
+    }
+
+    /// Pushes garbage rows into the bottom of the board, one for each hole column in `holes`, in
+    /// order: the last one ends up at the bottom. The stack and the active piece move up with
+    /// them, and the stack tops out if it's pushed out of the board or the active piece no longer
+    /// fits. Returns a message saying which hole is outside the board, without adding any rows, if
+    /// one is.
+    pub fn add_garbage(&mut self, holes: &[i32]) -> Result<(), String> {
+        if let Some(hole) = holes.iter().find(|hole| **hole < 0 || **hole >= self.cols) {
+            return Err(format!(
+                "the hole of a garbage row has to be between 0 and {}, not {}",
+                self.cols - 1,
+                hole
+            ));
         }
         if self.is_game_over {
-            self.game_over_rendered = true;
-            context.set_fill_style(&"white".into());
-            context.set_font("24px sans-serif");
-            context
-                .fill_text("Game Over", self.relative_x(10.0), self.relative_y(10.0))
-                .unwrap();
+            return Ok(());
         }
+
+        for hole in holes {
+            if self.cells[0].iter().any(Option::is_some) {
+                self.top_out();
+                // a puzzle that tops out has already moved on to the next one
+                if self.is_game_over || self.puzzles.is_some() {
+                    return Ok(());
+                }
+            }
+
+            self.cells.remove(0);
+            let row = (0..self.cols)
+                .map(|x| if x == *hole { None } else { Some(Cell::garbage()) })
+                .collect();
+            self.cells.push(row);
+            self.garbage_hole = Some(*hole);
+        }
+
+        // rows waiting to be cleared go up with the stack
+        for row in self.clearing_rows.iter_mut() {
+            *row -= holes.len() as i32;
+        }
+        if self.phase != Phase::Active {
+            return Ok(());
+        }
+
+        // the active piece goes up with the stack, but not out of the top of the board
+        let (x, y) = self.active_piece.get_origin().into();
+        let rise = (holes.len() as i32)
+            .min(self.active_piece.bounding_box().y1 + self.rules.hidden_rows);
+        self.active_piece.set_origin(x, y - rise);
+        self.lowest_row -= rise;
+
+        if !self.fits(self.active_piece.as_ref()) {
+            self.top_out();
+        }
+
+        Ok(())
+    }
+
+    /// Pushes `lines` garbage rows into the bottom of the board, see `add_garbage`. Each row has a
+    /// chance of `Rules::garbage_messiness` of having its hole in another column than the row
+    /// before it.
+    pub fn add_random_garbage(&mut self, lines: u32) {
+        let mut holes = Vec::with_capacity(lines as usize);
+        let mut previous = self.garbage_hole;
+
+        for _ in 0..lines {
+            let hole = match previous {
+                Some(hole) if self.garbage_rng.gen_range(0, 100) >= self.rules.garbage_messiness => hole,
+                Some(hole) => {
+                    // one of the other columns
+                    let other = self.garbage_rng.gen_range(0, self.cols as u32 - 1) as i32;
+                    if other >= hole {
+                        other + 1
+                    } else {
+                        other
+                    }
+                }
+                None => self.garbage_rng.gen_range(0, self.cols as u32) as i32,
+            };
+
+            holes.push(hole);
+            previous = Some(hole);
+        }
+
+        // the holes are all drawn inside the board
+        self.add_garbage(&holes).unwrap();
+    }
+
+    /// Deals the pieces that come after the ones already dealt with another randomizer, e.g. one
+    /// for a custom piece set. The pieces waiting in the preview are dealt again by the new one.
+    pub fn set_randomizer(&mut self, randomizer: Box<dyn Randomizer>) {
+        self.randomizer = randomizer;
+        self.queue.clear();
+        self.fill_queue();
+        // undoing would bring back the pieces of the old randomizer
+        self.history.clear();
+    }
+
+    pub fn held_piece(&self) -> Option<PieceKind> {
+        self.held_piece
     }
 
-    fn relative_x(&self, x: f64) -> f64 {
-        self.origin_x + x
+    /// Whether hold can still be used for the active piece
+    pub fn can_hold(&self) -> bool {
+        self.can_hold
     }
 
-    fn relative_y(&self, y: f64) -> f64 {
-        self.origin_y + y
+    /// The pieces that will be dealt next, first one first
+    pub fn preview(&self) -> Vec<PieceKind> {
+        self.queue.iter().take(self.rules.preview_length).copied().collect()
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn level(&self) -> u32 {
+        self.level
+    }
+
+    /// How many lines have been cleared
+    pub fn lines(&self) -> u32 {
+        self.lines
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    /// How many rows are visible, not counting the hidden ones above them
+    pub fn rows(&self) -> i32 {
+        self.rows
+    }
+
+    pub fn cols(&self) -> i32 {
+        self.cols
+    }
+
+    /// The placed cells of the visible rows, indexed as [row][col]
+    pub fn cells(&self) -> &[Vec<Option<Cell>>] {
+        &self.cells[self.rules.hidden_rows as usize..]
+    }
+
+    /// The placed cells of the hidden rows above the visible ones, indexed as [row][col], so the
+    /// last one is right above row 0
+    pub fn hidden_cells(&self) -> &[Vec<Option<Cell>>] {
+        &self.cells[..self.rules.hidden_rows as usize]
+    }
+
+    /// Fills a single cell, or empties it with `None`, e.g. to set up a board position. Hidden
+    /// rows have a negative y.
+    pub fn set_cell(&mut self, x: i32, y: i32, cell: Option<Cell>) {
+        let y = self.row_index(y);
+        self.cells[y][x as usize] = cell;
+    }
+
+    pub fn active_piece(&self) -> &dyn Piece {
+        self.active_piece.as_ref()
+    }
+
+    /// Replaces the active piece; the new piece is considered to be in its spawn rotation and to
+    /// have just appeared
+    pub fn set_active_piece(&mut self, piece: Box<dyn Piece>) {
+        self.lowest_row = piece.get_origin().y;
+        self.active_piece = piece;
+        self.rotation_state = RotationState::Spawn;
+        self.last_kick = None;
+        self.last_move_rotated = false;
+        self.lock_started = None;
+        self.lock_resets = 0;
+    }
+
+    pub fn rotation_state(&self) -> RotationState {
+        self.rotation_state
+    }
+
+    /// The kick that made the last rotation of the active piece fit, if it was rotated at all
+    pub fn last_kick(&self) -> Option<Kick> {
+        self.last_kick
+    }
+
+    /// Where the active piece would land if it was placed now
+    pub fn projection(&self) -> Vec<Position<i32>> {
+        self.project_piece(self.active_piece.as_ref())
+    }
+
+    /// The most recent clear of the game, if there was one
+    pub fn last_clear(&self) -> Option<&Clear> {
+        self.last_clear.as_ref()
+    }
+
+    /// Hands over the clears made since the last call, so effects can be played for them
+    pub fn take_clears(&mut self) -> Vec<Clear> {
+        std::mem::take(&mut self.clears)
     }
 
     pub fn pause(&mut self) {
@@ -466,19 +1484,56 @@ impl Board {
         self.paused_at = self.last_processed_tick;
     }
 
-    pub fn resume(&mut self) {
+    /// Carries on a paused game at the given game time. The board isn't updated while it's paused,
+    /// so every delay and the game clock are moved on by the time since the last update before the
+    /// pause.
+    pub fn resume(&mut self, time: f64) {
         if self.is_game_over {
             return;
         }
         self.is_paused = false;
-        self.last_drop = self.last_processed_tick - (self.paused_at - self.last_drop);
+        let paused_for = time - self.paused_at;
+        self.last_drop += paused_for;
+        if let Some(auto_shift) = self.auto_shift.as_mut() {
+            auto_shift.next_shift += paused_for;
+        }
+        self.phase = match self.phase {
+            Phase::Active => Phase::Active,
+            Phase::LineClear(until) => Phase::LineClear(until + paused_for),
+            Phase::Entry(until) => Phase::Entry(until + paused_for),
+        };
+        if let Some(lock_started) = self.lock_started {
+            self.lock_started = Some(lock_started + paused_for);
+        }
+        self.last_processed_tick = time;
+    }
+
+    /// What the board is doing: playing the active piece, or waiting to remove cleared rows or to
+    /// deal the next piece
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    /// The puzzles being played and how they're going, if any were loaded
+    pub fn puzzles(&self) -> Option<&PuzzleRun> {
+        self.puzzles.as_ref()
     }
 
     pub fn is_paused(&self) -> bool {
-        self.is_paused && self.paused_rendered
+        self.is_paused
     }
 
     pub fn is_game_over(&self) -> bool {
-        self.is_game_over && self.game_over_rendered
+        self.is_game_over
+    }
+
+    /// How the game ended, or None while it's still going
+    pub fn outcome(&self) -> Option<Outcome> {
+        self.outcome
+    }
+
+    /// How long the game has been played, in ms, not counting pauses
+    pub fn elapsed(&self) -> f64 {
+        self.elapsed
     }
 }
@@ -0,0 +1,434 @@
+use crate::animations::{Animation, Flash};
+use crate::board::{Board, Phase};
+use crate::cell::Cell;
+use crate::colors;
+use crate::game_mode::{self, GameMode, Outcome};
+use crate::pieces::PieceKind;
+use crate::scoring::Clear;
+
+const PANEL_CELL_SCALE: f64 = 0.5; // pieces in the side panels are drawn at half size
+const PANEL_SLOT_CELLS: f64 = 5.0; // each piece in a side panel gets a square of 5x5 (scaled) cells
+const PANEL_LABEL_HEIGHT: f64 = 30.0;
+const ANNOUNCEMENT_DURATION: f64 = 1500.0; // ms a clear stays announced next to the board
+const PEEK_ALPHA: f64 = 0.5; // the hidden rows that are peeked at are drawn faded out
+
+/// How wide each of the side panels is, with the gap between it and the board, in cells
+pub const SIDE_PANEL_CELLS: f64 = 1.0 + PANEL_CELL_SCALE * PANEL_SLOT_CELLS;
+
+/// BoardView draws a `Board` onto the canvas and owns everything that only matters for rendering:
+/// the board's location and size in pixels and the animations that are currently playing.
+pub struct BoardView {
+    pixels_per_cell: i32,
+    pixel_width: f64,  // width of board in pixels
+    pixel_height: f64, // height of board in pixels
+
+    origin_x: f64, // x coord on context where the board resides
+    origin_y: f64, // y coord on context where the board resides
+    peek_rows: i32, // how many of the hidden rows are drawn above the board
+
+    paused_rendered: bool,
+    game_over_rendered: bool,
+
+    announcement: Option<(Clear, f64)>, // the clear being announced and when it happened
+
+    animations: Vec<Box<dyn Animation>>,
+}
+
+impl BoardView {
+    pub fn new(
+        board: &Board,
+        pixels_per_cell: i32,
+        origin_x: f64,
+        origin_y: f64,
+        peek_rows: i32,
+    ) -> Self {
+        BoardView {
+            pixels_per_cell,
+            pixel_width: (board.cols() * pixels_per_cell) as f64,
+            pixel_height: (board.rows() * pixels_per_cell) as f64,
+            origin_x,
+            origin_y,
+            peek_rows: peek_rows.min(board.hidden_cells().len() as i32),
+            paused_rendered: false,
+            game_over_rendered: false,
+            announcement: None,
+            animations: Vec::with_capacity(40),
+        }
+    }
+
+    /// Starts effects for whatever happened on the board since the last update and advances the
+    /// running animations
+    pub fn update(&mut self, board: &mut Board, time: f64) {
+        let peek_rows = self.peek_rows;
+        for clear in board.take_clears() {
+            // cleared rows are only flashed where they are drawn
+            for row in clear.rows.iter().filter(|row| **row >= -peek_rows) {
+                for i in 0..board.cols() {
+                    self.animations.push(Box::new(Flash::new(
+                        self.origin_x + i as f64 * self.pixels_per_cell as f64,
+                        self.origin_y + *row as f64 * self.pixels_per_cell as f64,
+                        time,
+                        board.rules().line_clear_delay,
+                    )));
+                }
+            }
+
+            self.announcement = Some((clear, time));
+        }
+
+        if let Some((_, announced_at)) = self.announcement {
+            if time - announced_at > ANNOUNCEMENT_DURATION {
+                self.announcement = None;
+            }
+        }
+
+        for animation in self.animations.iter_mut() {
+            animation.update(time);
+        }
+    }
+
+    pub fn draw(&mut self, board: &Board, context: &web_sys::CanvasRenderingContext2d) {
+        // draw border
+        context.set_stroke_style(&colors::BORDER.into());
+        context.set_line_width(1.0);
+        context.set_line_dash(&js_sys::Array::new()).unwrap();
+        context.begin_path();
+        context.move_to(self.relative_x(0.0), self.relative_y(0.0));
+        context.line_to(self.relative_x(self.pixel_width), self.relative_y(0.0));
+        context.line_to(
+            self.relative_x(self.pixel_width),
+            self.relative_y(self.pixel_height),
+        );
+        context.line_to(self.relative_x(0.0), self.relative_y(self.pixel_height));
+        context.line_to(self.relative_x(0.0), self.relative_y(0.0));
+
+        context.stroke();
+
+        // draw cross hatch
+        context.begin_path();
+        context.set_line_dash(&js_sys::Array::new()).unwrap();
+
+        for i in 1..board.cols() {
+            context.move_to(
+                self.relative_x((i * self.pixels_per_cell).into()),
+                self.origin_y,
+            );
+
+            context.line_to(
+                self.relative_x((i * self.pixels_per_cell).into()),
+                self.relative_y(self.pixel_height),
+            );
+        }
+
+        for j in 1..board.rows() {
+            context.move_to(
+                self.origin_x,
+                self.relative_y((j * self.pixels_per_cell).into()),
+            );
+
+            context.line_to(
+                self.relative_x(self.pixel_width),
+                self.relative_y((j * self.pixels_per_cell).into()),
+            );
+        }
+
+        context.stroke();
+
+        // draw pieces on the board, each cell in the style of the piece it came from, and the ones
+        // in the hidden rows that are peeked at faded out
+        self.draw_cells(context, board.cells(), 0);
+        let hidden = board.hidden_cells();
+        context.set_global_alpha(PEEK_ALPHA);
+        self.draw_cells(
+            context,
+            &hidden[hidden.len() - self.peek_rows as usize..],
+            -self.peek_rows,
+        );
+        context.set_global_alpha(1.0);
+
+        // the active piece and its projection can be in the hidden rows, so only the ones that are
+        // peeked at are drawn
+        let peek_height = (self.peek_rows * self.pixels_per_cell) as f64;
+        context.save();
+        context.begin_path();
+        context.rect(
+            self.relative_x(0.0),
+            self.relative_y(-peek_height),
+            self.pixel_width,
+            self.pixel_height + peek_height,
+        );
+        context.clip();
+
+        // draw active piece, if there is one in play
+        let is_piece_in_play = !board.is_game_over() && board.phase() == Phase::Active;
+        if is_piece_in_play {
+            let origin = board.active_piece().get_origin();
+            board.active_piece().draw(
+                context,
+                self.origin_x + (origin.x * self.pixels_per_cell) as f64,
+                self.origin_y + (origin.y * self.pixels_per_cell) as f64,
+                self.pixels_per_cell as f64,
+            );
+        }
+
+        // draw the projection
+        if is_piece_in_play {
+            context.set_stroke_style(&colors::PROJECTION_STROKE.into());
+            context
+                .set_line_dash(&js_sys::Array::of2(&3.into(), &3.into()))
+                .unwrap();
+            context.begin_path();
+
+            let mask = board.projection();
+
+            for item in mask {
+                context.move_to(
+                    self.origin_x + (item.x * self.pixels_per_cell) as f64,
+                    self.origin_y + (item.y * self.pixels_per_cell) as f64,
+                );
+                context.line_to(
+                    self.origin_x + ((item.x + 1) * self.pixels_per_cell) as f64,
+                    self.origin_y + (item.y * self.pixels_per_cell) as f64,
+                );
+                context.line_to(
+                    self.origin_x + ((item.x + 1) * self.pixels_per_cell) as f64,
+                    self.origin_y + ((item.y + 1) * self.pixels_per_cell) as f64,
+                );
+                context.line_to(
+                    self.origin_x + (item.x * self.pixels_per_cell) as f64,
+                    self.origin_y + ((item.y + 1) * self.pixels_per_cell) as f64,
+                );
+                context.line_to(
+                    self.origin_x + (item.x * self.pixels_per_cell) as f64,
+                    self.origin_y + (item.y * self.pixels_per_cell) as f64,
+                );
+            }
+
+            context.stroke();
+        }
+        context.restore();
+
+        self.draw_preview(board, context);
+        self.draw_hold(board, context);
+        self.draw_announcement(context);
+
+        // draw animations
+        for animation in &self.animations {
+            animation.draw(context, 0.0, 0.0, self.pixels_per_cell as f64);
+        }
+
+        self.paused_rendered = board.is_paused();
+        if board.is_paused() {
+            web_sys::console::log_1(&"drawing pause".into());
+            context.set_fill_style(&"white".into());
+            context.set_font("24px sans-serif");
+            context
+                .fill_text("Paused", self.relative_x(10.0), self.relative_y(10.0))
+                .unwrap();
+        }
+        if board.is_game_over() {
+            self.game_over_rendered = true;
+            self.draw_results(board, context);
+        }
+    }
+
+    /// Covers the board with how the game ended and what was achieved, picking out the result the
+    /// mode is about
+    fn draw_results(&self, board: &Board, context: &web_sys::CanvasRenderingContext2d) {
+        let mode = board.rules().mode;
+        let title = match (mode, board.outcome()) {
+            (GameMode::Ultra, Some(Outcome::Completed)) => "Time's Up",
+            (_, Some(Outcome::Completed)) => "Complete!",
+            _ => "Game Over",
+        };
+
+        let score = format!("Score: {}", board.score);
+        let time = format!("Time: {}", game_mode::format_time(board.elapsed()));
+        let headline = match (mode, board.outcome(), board.puzzles()) {
+            (GameMode::Sprint, Some(Outcome::Completed), _) => time.clone(),
+            (GameMode::Puzzle, _, Some(run)) => format!(
+                "Solved: {}/{}",
+                run.solved.iter().filter(|solved| **solved).count(),
+                run.puzzles.len()
+            ),
+            _ => score.clone(),
+        };
+
+        let mut lines = vec![format!("{} {}", mode.name(), title), headline.clone()];
+        let stats = vec![
+            score,
+            format!("Lines: {}", board.lines()),
+            format!("Level: {}", board.level()),
+            time,
+        ];
+        lines.extend(stats.into_iter().filter(|stat| *stat != headline));
+
+        context.set_fill_style(&"rgba(0, 0, 0, 0.7)".into());
+        context.fill_rect(
+            self.relative_x(0.0),
+            self.relative_y(0.0),
+            self.pixel_width,
+            self.pixel_height,
+        );
+
+        context.set_fill_style(&"white".into());
+        context.set_text_baseline("top");
+        for (i, line) in lines.iter().enumerate() {
+            // the first line is the title and the second one the result that matters for the mode
+            context.set_font(if i < 2 { "24px sans-serif" } else { "18px sans-serif" });
+            context
+                .fill_text(
+                    line,
+                    self.relative_x(10.0),
+                    self.relative_y(10.0 + i as f64 * PANEL_LABEL_HEIGHT),
+                )
+                .unwrap();
+        }
+    }
+
+    /// Draws the upcoming pieces in a column to the right of the board
+    fn draw_preview(&self, board: &Board, context: &web_sys::CanvasRenderingContext2d) {
+        let preview = board.preview();
+        if preview.is_empty() {
+            return;
+        }
+
+        let x = self.relative_x(self.pixel_width + self.pixels_per_cell as f64);
+        let y = self.relative_y(0.0);
+        self.draw_panel(context, "Next", &preview, preview.len(), x, y);
+    }
+
+    /// Draws the held piece in a panel to the left of the board, faded out while hold can't be used
+    fn draw_hold(&self, board: &Board, context: &web_sys::CanvasRenderingContext2d) {
+        let slot = self.pixels_per_cell as f64 * PANEL_CELL_SCALE * PANEL_SLOT_CELLS;
+        let x = self.relative_x(-(self.pixels_per_cell as f64) - slot);
+        let y = self.relative_y(0.0);
+        let held: Vec<PieceKind> = board.held_piece().into_iter().collect();
+
+        if !board.can_hold() {
+            context.set_global_alpha(0.4);
+        }
+        self.draw_panel(context, "Hold", &held, 1, x, y);
+        context.set_global_alpha(1.0);
+    }
+
+    /// Names the latest clear under the hold panel, e.g. "Back-to-Back Tetris"
+    fn draw_announcement(&self, context: &web_sys::CanvasRenderingContext2d) {
+        let clear = match &self.announcement {
+            Some((clear, _)) => clear,
+            None => return,
+        };
+
+        let mut lines = Vec::with_capacity(4);
+        if clear.back_to_back {
+            lines.push("Back-to-Back".to_string());
+        }
+        lines.push(clear.clear_type.name().to_string());
+        if clear.chain > 0 {
+            lines.push(format!("{} Chain", clear.chain));
+        }
+        if clear.combo > 0 {
+            lines.push(format!("{} Combo", clear.combo));
+        }
+
+        let slot = self.pixels_per_cell as f64 * PANEL_CELL_SCALE * PANEL_SLOT_CELLS;
+        let x = self.relative_x(-(self.pixels_per_cell as f64));
+        let y = self.relative_y(2.0 * PANEL_LABEL_HEIGHT + slot);
+
+        context.set_fill_style(&"white".into());
+        context.set_font("24px sans-serif");
+        context.set_text_baseline("top");
+        context.set_text_align("right");
+        for (i, line) in lines.iter().enumerate() {
+            context
+                .fill_text(line, x, y + i as f64 * PANEL_LABEL_HEIGHT)
+                .unwrap();
+        }
+        context.set_text_align("start");
+    }
+
+    /// Draws a labelled column of `slots` slots with its top left corner at (x, y), filling the
+    /// first ones with `pieces`
+    fn draw_panel(
+        &self,
+        context: &web_sys::CanvasRenderingContext2d,
+        label: &str,
+        pieces: &[PieceKind],
+        slots: usize,
+        x: f64,
+        y: f64,
+    ) {
+        let cell = self.pixels_per_cell as f64 * PANEL_CELL_SCALE;
+        let slot = cell * PANEL_SLOT_CELLS;
+
+        context.set_fill_style(&"black".into());
+        context.set_font("24px sans-serif");
+        context.set_text_baseline("top");
+        context.fill_text(label, x, y).unwrap();
+
+        context.set_stroke_style(&colors::BORDER.into());
+        context.set_line_width(1.0);
+        context.set_line_dash(&js_sys::Array::new()).unwrap();
+        context.stroke_rect(x, y + PANEL_LABEL_HEIGHT, slot, slot * slots as f64);
+
+        for (i, kind) in pieces.iter().enumerate() {
+            let piece = kind.create(0, 0);
+            let bb = piece.bounding_box();
+            let width = (bb.x2 - bb.x1 + 1) as f64 * cell;
+            let height = (bb.y2 - bb.y1 + 1) as f64 * cell;
+
+            // center the piece in its slot
+            piece.draw(
+                context,
+                x + (slot - width) / 2.0 - bb.x1 as f64 * cell,
+                y + PANEL_LABEL_HEIGHT + i as f64 * slot + (slot - height) / 2.0
+                    - bb.y1 as f64 * cell,
+                cell,
+            );
+        }
+    }
+
+    /// Draws rows of placed cells, the first of them being row `first_row` of the board
+    fn draw_cells(
+        &self,
+        context: &web_sys::CanvasRenderingContext2d,
+        rows: &[Vec<Option<Cell>>],
+        first_row: i32,
+    ) {
+        for (y, row) in (first_row..).zip(rows.iter()) {
+            for (x, cell) in row.iter().enumerate() {
+                if let Some(cell) = cell {
+                    context.set_stroke_style(&cell.style.stroke.into());
+                    context.set_fill_style(&cell.style.fill.into());
+                    context.begin_path();
+                    context.rect(
+                        self.relative_x((x as i32 * self.pixels_per_cell) as f64),
+                        self.relative_y((y * self.pixels_per_cell) as f64),
+                        self.pixels_per_cell as f64,
+                        self.pixels_per_cell as f64,
+                    );
+                    context.stroke();
+                    context.fill();
+                }
+            }
+        }
+    }
+
+    fn relative_x(&self, x: f64) -> f64 {
+        self.origin_x + x
+    }
+
+    fn relative_y(&self, y: f64) -> f64 {
+        self.origin_y + y
+    }
+
+    /// The game is paused and the pause screen has been drawn, so there is nothing left to render
+    pub fn is_paused(&self, board: &Board) -> bool {
+        board.is_paused() && self.paused_rendered
+    }
+
+    /// The game is over and the game over screen has been drawn
+    pub fn is_game_over(&self, board: &Board) -> bool {
+        board.is_game_over() && self.game_over_rendered
+    }
+}
@@ -0,0 +1,32 @@
+use crate::colors::{self, Style};
+use crate::pieces::PieceKind;
+use serde::Serialize;
+
+/// Cell is a square of the board taken by a locked piece or by garbage. It remembers which kind of
+/// piece it came from and how it's drawn.
+#[derive(Serialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Cell {
+    pub kind: Option<PieceKind>, // None for garbage, which wasn't placed by a piece
+    pub style: Style,
+}
+
+impl Cell {
+    /// A cell of a piece of the given kind, in that piece's style
+    pub const fn new(kind: PieceKind) -> Self {
+        Cell {
+            kind: Some(kind),
+            style: kind.style(),
+        }
+    }
+
+    /// A cell of a garbage row
+    pub const fn garbage() -> Self {
+        Cell {
+            kind: None,
+            style: Style {
+                stroke: colors::GARBAGE_STROKE,
+                fill: colors::GARBAGE_FILL,
+            },
+        }
+    }
+}
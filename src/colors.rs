@@ -1,18 +1,34 @@
-pub const BORDER: &'static str = "rgb(125, 125, 125)";
+use serde::Serialize;
 
-pub const BAR_STROKE: &'static str = "rgba(200, 0, 0, 200)";
-pub const BAR_FILL: &'static str = "#09090988";
+pub const BORDER: &str = "rgb(125, 125, 125)";
 
-pub const SQUARE_FILL: &'static str = "rgb(0, 200, 0)";
-pub const SQUARE_STROKE: &'static str = "rgb(0, 255, 0)";
+pub const BAR_STROKE: &str = "rgba(200, 0, 0, 200)";
+pub const BAR_FILL: &str = "#09090988";
 
-pub const LPIECE_STROKE: &'static str = "rgb(200, 0 , 200)";
-pub const LPIECE_FILL: &'static str = "rgb(100, 0, 100)";
+pub const SQUARE_FILL: &str = "rgb(0, 200, 0)";
+pub const SQUARE_STROKE: &str = "rgb(0, 255, 0)";
 
-pub const ZPIECE_STROKE: &'static str = "rgb(0, 200 , 200)";
-pub const ZPIECE_FILL: &'static str = "rgb(0, 100, 100)";
+pub const LPIECE_STROKE: &str = "rgb(200, 0 , 200)";
+pub const LPIECE_FILL: &str = "rgb(100, 0, 100)";
 
-pub const PROJECTION_STROKE: &'static str = "rgb(255, 255, 255)";
+pub const ZPIECE_STROKE: &str = "rgb(0, 200 , 200)";
+pub const ZPIECE_FILL: &str = "rgb(0, 100, 100)";
 
-pub const CELL_COMPLETE_COLOR: &'static str = "rgb(255,255,255)";
-pub const CELL_COMPLETE_FILL: &'static str = "rgb(255,255,255)";
+pub const ERASER_STROKE: &str = "rgb(255, 60, 60)";
+pub const ERASER_FILL: &str = "rgba(255, 255, 255, 0.2)";
+
+pub const GARBAGE_STROKE: &str = "rgb(160, 160, 160)";
+pub const GARBAGE_FILL: &str = "rgb(90, 90, 90)";
+
+pub const PROJECTION_STROKE: &str = "rgb(255, 255, 255)";
+
+pub const CELL_COMPLETE_COLOR: &str = "rgb(255,255,255)";
+pub const CELL_COMPLETE_FILL: &str = "rgb(255,255,255)";
+
+/// Style is how the cells of a piece are outlined and filled, both while it's falling and after it
+/// has been locked into the board
+#[derive(Serialize, Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Style {
+    pub stroke: &'static str,
+    pub fill: &'static str,
+}
@@ -0,0 +1,103 @@
+use crate::board_view;
+use crate::rules::Rules;
+use serde::Deserialize;
+
+/// The fewest rows and columns a board can have, so that every built in piece can spawn
+pub const MIN_BOARD_SIZE: i32 = 4;
+/// The most rows and columns a board can have
+pub const MAX_BOARD_SIZE: i32 = 100;
+
+/// Config is how a page sets up a game: the size of the board, where and how big it's drawn, and
+/// the rules it's played by. It's read from JSON where every field can be left out, e.g.
+/// `{ "cols": 16, "auto_fit": true, "rules": { "mode": "sprint" } }`.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub rows: i32,
+    pub cols: i32,
+    pub cell_size: i32,             // pixels per cell, unless auto_fit is set
+    pub auto_fit: bool, // use the biggest cells that fit the board and its side panels on the canvas
+    pub origin: Option<(f64, f64)>, // the top left corner of the board on the canvas, centered at the top if not set
+    pub peek_rows: i32,             // how many of the hidden rows are drawn above the board
+    pub canvas_id: String,          // the id of the canvas element to draw on
+    pub seed: Option<u32>,          // picked at random if not set
+    pub randomizer: String,         // one of the names `randomizers::from_name` knows
+    pub rules: Rules,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            rows: 20,
+            cols: 10,
+            cell_size: 30,
+            auto_fit: false,
+            origin: None,
+            peek_rows: 2,
+            canvas_id: "canvas".to_string(),
+            seed: None,
+            randomizer: "7-bag".to_string(),
+            rules: Rules::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Reads a config from JSON and checks it, saying what's wrong if it isn't valid
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let config: Config =
+            serde_json::from_str(json).map_err(|error| format!("invalid config: {}", error))?;
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    /// Checks that the settings make sense, saying what's wrong if they don't
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, size) in &[("rows", self.rows), ("cols", self.cols)] {
+            if *size < MIN_BOARD_SIZE || *size > MAX_BOARD_SIZE {
+                return Err(format!(
+                    "{} has to be between {} and {}, not {}",
+                    name, MIN_BOARD_SIZE, MAX_BOARD_SIZE, size
+                ));
+            }
+        }
+        if !self.auto_fit && self.cell_size < 1 {
+            return Err(format!(
+                "cell_size has to be at least 1 pixel, not {}",
+                self.cell_size
+            ));
+        }
+        if self.peek_rows < 0 || self.peek_rows > self.rules.hidden_rows {
+            return Err(format!(
+                "peek_rows has to be between 0 and hidden_rows ({}), not {}",
+                self.rules.hidden_rows, self.peek_rows
+            ));
+        }
+        if self.canvas_id.is_empty() {
+            return Err("canvas_id can't be empty".to_string());
+        }
+
+        self.rules.validate()
+    }
+
+    /// The size of a cell in pixels, fitting the board, the hidden rows that are peeked at and its
+    /// side panels on a canvas of the given size if `auto_fit` is set
+    pub fn pixels_per_cell(&self, canvas_width: f64, canvas_height: f64) -> Result<i32, String> {
+        if !self.auto_fit {
+            return Ok(self.cell_size);
+        }
+
+        let width = self.cols as f64 + 2.0 * board_view::SIDE_PANEL_CELLS;
+        let height = (self.rows + self.peek_rows) as f64;
+        let size = (canvas_width / width).min(canvas_height / height) as i32;
+        if size < 1 {
+            return Err(format!(
+                "a board of {} by {} doesn't fit on a canvas of {} by {} pixels",
+                self.cols, self.rows, canvas_width, canvas_height
+            ));
+        }
+
+        Ok(size)
+    }
+}
@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+
+/// The level a game of Marathon has to reach
+pub const MARATHON_LEVEL: u32 = 15;
+/// The lines a game of Sprint has to clear
+pub const SPRINT_LINES: u32 = 40;
+/// How long a game of Ultra lasts, in ms
+pub const ULTRA_TIME: f64 = 120_000.0;
+
+/// GameMode sets the goal of a game and how it ends. It's written in lower case, e.g. "sprint".
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum GameMode {
+    Endless,  // no goal, the game goes on until the stack tops out
+    Marathon, // reach level MARATHON_LEVEL
+    Sprint,   // clear SPRINT_LINES lines as fast as possible
+    Ultra,    // score as much as possible in ULTRA_TIME
+    Zen,      // no goal and no top-out: the board is emptied instead
+    Puzzle,   // solve the puzzles loaded with `Board::load_puzzles`, one after another
+}
+
+/// How a game ended
+#[derive(Serialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Completed, // the goal of the mode was reached, or its time ran out
+    ToppedOut, // the stack reached the top of the board first
+}
+
+impl GameMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            GameMode::Endless => "Endless",
+            GameMode::Marathon => "Marathon",
+            GameMode::Sprint => "Sprint",
+            GameMode::Ultra => "Ultra",
+            GameMode::Zen => "Zen",
+            GameMode::Puzzle => "Puzzle",
+        }
+    }
+
+    /// Has a game with the given progress reached the goal of the mode
+    pub fn is_complete(self, level: u32, lines: u32, elapsed: f64) -> bool {
+        match self {
+            GameMode::Marathon => level >= MARATHON_LEVEL,
+            GameMode::Sprint => lines >= SPRINT_LINES,
+            GameMode::Ultra => elapsed >= ULTRA_TIME,
+            // puzzles end the game themselves, once the last one is finished
+            GameMode::Endless | GameMode::Zen | GameMode::Puzzle => false,
+        }
+    }
+
+    /// How long the game lasts, in ms, if the mode has a time limit
+    pub fn time_limit(self) -> Option<f64> {
+        match self {
+            GameMode::Ultra => Some(ULTRA_TIME),
+            _ => None,
+        }
+    }
+
+    /// Does the game end when the stack reaches the top of the board; in Zen the board is emptied
+    /// instead, and a puzzle that tops out fails
+    pub fn can_top_out(self) -> bool {
+        self != GameMode::Zen && self != GameMode::Puzzle
+    }
+
+    /// Can placements be undone; only in the modes that are for practice, where nothing is being
+    /// competed for
+    pub fn can_undo(self) -> bool {
+        matches!(self, GameMode::Zen | GameMode::Puzzle)
+    }
+}
+
+/// Formats a duration in ms as minutes, seconds and hundredths, e.g. "1:05.20"
+pub fn format_time(ms: f64) -> String {
+    let hundredths = (ms.max(0.0) / 10.0) as u64;
+    format!(
+        "{}:{:02}.{:02}",
+        hundredths / 6000,
+        hundredths / 100 % 60,
+        hundredths % 100
+    )
+}
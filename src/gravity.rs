@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+
+/// How fast pieces fall on their own
+#[derive(Serialize, Copy, Clone, Debug, PartialEq)]
+pub enum Gravity {
+    Interval(f64), // ms it takes a piece to fall by 1 square
+    Instant,       // pieces drop to the stack as soon as they appear (20G)
+}
+
+/// What happens to the cells above a row when it's cleared. It's written in lower case, e.g.
+/// "cascade".
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ClearGravity {
+    Naive,   // every row above moves down by one, keeping any holes under it
+    Cascade, // connected cells fall together as far as they can, possibly clearing more rows
+}
+
+/// The first level pieces drop instantly at
+pub const INSTANT_GRAVITY_LEVEL: u32 = 20;
+
+/// ms per square for levels 1 to 19, following the curve (0.8 - (level - 1) * 0.007) ^ (level - 1)
+/// seconds used by most modern games
+const FALL_INTERVALS: [f64; 19] = [
+    1000.0, 793.0, 617.8, 472.7, 355.2, 262.0, 189.7, 134.7, 93.9, 64.2, 43.0, 28.2, 18.2, 11.4,
+    7.1, 4.3, 2.5, 1.5, 0.8,
+];
+
+/// The gravity at the given level. Levels start at 1; anything from `INSTANT_GRAVITY_LEVEL` up is
+/// 20G.
+pub fn gravity(level: u32) -> Gravity {
+    if level >= INSTANT_GRAVITY_LEVEL {
+        return Gravity::Instant;
+    }
+
+    Gravity::Interval(FALL_INTERVALS[level.max(1) as usize - 1])
+}
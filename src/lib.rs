@@ -3,35 +3,46 @@ use wasm_bindgen::JsCast;
 use std::cell::RefCell;
 use std::rc::Rc;
 
-mod board;
-mod colors;
-mod geometry;
-mod pieces;
-mod animations;
+pub mod animations;
+pub mod board;
+mod board_view;
+pub mod cell;
+pub mod colors;
+pub mod config;
+pub mod game_mode;
+pub mod geometry;
+pub mod gravity;
+pub mod pieces;
+pub mod puzzle;
+pub mod randomizers;
+pub mod rotation;
+pub mod rules;
+pub mod scoring;
 
 use board::Board;
-
-const NUM_COLS: i32 = 10;
-const NUM_ROWS: i32 = 20;
-const PIXELS_PER_CELL: i32 = 30;
+use config::Config;
+use pieces::piece_set;
+use randomizers::WeightedRandomizer;
+use board_view::BoardView;
 
 fn window() -> web_sys::Window {
     web_sys::window().expect("no global `window` exists")
 }
 
-fn context() -> web_sys::CanvasRenderingContext2d {
+fn context(canvas_id: &str) -> Result<web_sys::CanvasRenderingContext2d, JsValue> {
     let window = window();
     let document = window.document().unwrap();
-    let canvas = document.get_element_by_id("canvas").unwrap();
+    let canvas = document
+        .get_element_by_id(canvas_id)
+        .ok_or_else(|| JsValue::from(format!("there is no element with the id {:?}", canvas_id)))?;
     let canvas: web_sys::HtmlCanvasElement = canvas
         .dyn_into::<web_sys::HtmlCanvasElement>()
-        .map_err(|_| ())
-        .unwrap();
+        .map_err(|_| JsValue::from(format!("the element {:?} isn't a canvas", canvas_id)))?;
 
     let context = canvas
         .get_context_with_context_options(
             "2d",
-            &JsValue::from_serde(&vec![("alpha", false)]).unwrap(),
+            &js_sys::JSON::parse(r#"{ "alpha": false }"#).unwrap(),
         )
         .unwrap()
         .unwrap()
@@ -46,7 +57,7 @@ fn context() -> web_sys::CanvasRenderingContext2d {
     canvas.set_width(window.inner_width().unwrap().as_f64().unwrap() as u32);
     canvas.set_height(window.inner_height().unwrap().as_f64().unwrap() as u32);
 
-    return context;
+    Ok(context)
 }
 
 fn request_animation_frame(f: &Closure<dyn FnMut(f64)>) {
@@ -55,30 +66,138 @@ fn request_animation_frame(f: &Closure<dyn FnMut(f64)>) {
         .expect("should register `requestAnimationFrame` OK");
 }
 
+/// GameHandle is what `run` hands back to the page, so that it can read the state of the game
+#[wasm_bindgen]
+pub struct GameHandle {
+    board: Rc<RefCell<Board>>,
+}
+
 #[wasm_bindgen]
-pub fn run() -> Result<(), JsValue> {
+impl GameHandle {
+    /// The names of the upcoming pieces, next one first, e.g. `["Line", "Square"]`
+    pub fn preview(&self) -> JsValue {
+        let preview = serde_json::to_string(&self.board.borrow().preview()).unwrap();
+        js_sys::JSON::parse(&preview).unwrap()
+    }
+
+    /// The most recent clear, e.g. `{ clear_type: "Tetris", back_to_back: true, combo: 0, ... }`, or
+    /// null before the first one
+    pub fn last_clear(&self) -> JsValue {
+        let clear = serde_json::to_string(&self.board.borrow().last_clear()).unwrap();
+        js_sys::JSON::parse(&clear).unwrap()
+    }
+
+    /// The name of the held piece, or null when nothing is held
+    pub fn held_piece(&self) -> JsValue {
+        let held = serde_json::to_string(&self.board.borrow().held_piece()).unwrap();
+        js_sys::JSON::parse(&held).unwrap()
+    }
+
+    /// How the game ended, "Completed" or "ToppedOut", or null while it's still going
+    pub fn outcome(&self) -> JsValue {
+        let outcome = serde_json::to_string(&self.board.borrow().outcome()).unwrap();
+        js_sys::JSON::parse(&outcome).unwrap()
+    }
+
+    /// Replaces the pieces that are dealt from now on with a set described in JSON, e.g.
+    /// `[{ "name": "I3", "cells": [[-1, 0], [0, 0], [1, 0]], "stroke": "red", "fill": "darkred",
+    /// "weight": 1 }, ...]` (see `pieces::piece_set` for the details). Throws an error saying what's
+    /// wrong with the set if it isn't valid.
+    pub fn load_piece_set(&self, json: &str) -> Result<(), JsValue> {
+        let pieces = piece_set::parse(json).map_err(JsValue::from)?;
+        self.board
+            .borrow_mut()
+            .set_randomizer(Box::new(WeightedRandomizer::new(pieces)));
+
+        Ok(())
+    }
+
+    /// Replaces the game with a list of puzzles, written in JSON or as text (see `puzzle` for
+    /// both), which are played one after another. Throws an error saying what's wrong if the
+    /// puzzles aren't valid or don't fit on the board.
+    pub fn load_puzzles(&self, source: &str) -> Result<(), JsValue> {
+        let puzzles = puzzle::parse(source).map_err(JsValue::from)?;
+        self.board
+            .borrow_mut()
+            .load_puzzles(puzzles)
+            .map_err(JsValue::from)
+    }
+
+    /// The puzzles being played, e.g. `{ current: 1, solved: [true], lines: 0, ... }`, or null
+    /// when no puzzles were loaded
+    pub fn puzzles(&self) -> JsValue {
+        let puzzles = serde_json::to_string(&self.board.borrow().puzzles()).unwrap();
+        js_sys::JSON::parse(&puzzles).unwrap()
+    }
+
+    /// Takes back the last `placements` pieces that locked, in Zen and puzzles, and returns how
+    /// many could be
+    pub fn undo(&self, placements: usize) -> usize {
+        self.board.borrow_mut().undo(placements)
+    }
+
+    /// Pushes `lines` garbage rows into the bottom of the board, e.g. the lines sent by an opponent
+    pub fn add_garbage(&self, lines: u32) {
+        self.board.borrow_mut().add_random_garbage(lines);
+    }
+}
+
+/// Starts the game as set up by `config`, an object with the fields of `config::Config`, all of
+/// which can be left out, e.g. `{ cols: 16, auto_fit: true, rules: { mode: "sprint" } }`. Passing
+/// the `seed` of an earlier game deals the same pieces again; without one a random seed is picked
+/// (and logged to the console). Throws an error saying what's wrong if the config isn't valid.
+#[wasm_bindgen]
+pub fn run(config: JsValue) -> Result<GameHandle, JsValue> {
+    let config = if config.is_undefined() || config.is_null() {
+        Config::default()
+    } else {
+        let json = String::from(js_sys::JSON::stringify(&config)?);
+        Config::from_json(&json)?
+    };
+
     let f = Rc::new(RefCell::new(None));
     let g = f.clone();
     let h = f.clone();
-    let context = context();
+    let context = context(&config.canvas_id)?;
 
     // setup board and its location
-    let board_half_width: i32 = NUM_COLS * PIXELS_PER_CELL / 2;
-    let half_screen: i32 = context.canvas().unwrap().width() as i32 / 2;
+    let canvas = context.canvas().unwrap();
+    let pixels_per_cell = config.pixels_per_cell(canvas.width() as f64, canvas.height() as f64)?;
+    let (origin_x, origin_y) = config.origin.unwrap_or((
+        ((canvas.width() as i32 - config.cols * pixels_per_cell) / 2) as f64,
+        (config.peek_rows * pixels_per_cell) as f64,
+    ));
+
+    let seed = config
+        .seed
+        .unwrap_or_else(|| (js_sys::Math::random() * u32::MAX as f64) as u32);
+    web_sys::console::log_2(&"seed".into(), &JsValue::from(seed));
+
+    let randomizer = randomizers::from_name(&config.randomizer)
+        .ok_or_else(|| JsValue::from(format!("unknown randomizer {}", config.randomizer)))?;
 
     let the_board = Rc::new(RefCell::new(Board::new(
-        NUM_ROWS,
-        NUM_COLS,
-        PIXELS_PER_CELL,
-        (half_screen - board_half_width) as f64,
-        0f64,
+        config.rows,
+        config.cols,
+        seed.into(),
+        randomizer,
+        config.rules,
     )));
+    let mut view = BoardView::new(
+        &the_board.borrow(),
+        pixels_per_cell,
+        origin_x,
+        origin_y,
+        config.peek_rows,
+    );
 
     {
         let board1 = the_board.clone();
         let keydown_closure = Closure::wrap(Box::new(move |event: web_sys::KeyboardEvent| {
             web_sys::console::log_2(&"got keypress".into(), &(&event).into());
-            board1.borrow_mut().keydown(&event);
+            board1
+                .borrow_mut()
+                .keydown(&event.code(), event.time_stamp());
         }) as Box<dyn FnMut(_)>);
 
         let board2 = the_board.clone();
@@ -88,13 +207,26 @@ pub fn run() -> Result<(), JsValue> {
             match event.data().as_string().unwrap().as_ref() {
                 "stop" => board2.borrow_mut().pause(),
                 "start" => {
-                    board2.borrow_mut().resume();
+                    board2.borrow_mut().resume(event.time_stamp());
                     request_animation_frame(h.borrow().as_ref().unwrap());
                 }
                 _ => (),
             }
         }) as Box<dyn FnMut(_)>);
 
+        let board3 = the_board.clone();
+        let keyup_closure = Closure::wrap(Box::new(move |event: web_sys::KeyboardEvent| {
+            board3
+                .borrow_mut()
+                .keyup(&event.code(), event.time_stamp());
+        }) as Box<dyn FnMut(_)>);
+
+        // keys held while the page loses focus never get their keyup
+        let board4 = the_board.clone();
+        let blur_closure = Closure::wrap(Box::new(move |_event: web_sys::Event| {
+            board4.borrow_mut().release_keys();
+        }) as Box<dyn FnMut(_)>);
+
         let document = window().document().unwrap();
 
         document
@@ -102,38 +234,103 @@ pub fn run() -> Result<(), JsValue> {
             .unwrap();
         keydown_closure.forget();
 
+        document
+            .add_event_listener_with_callback("keyup", keyup_closure.as_ref().unchecked_ref())
+            .unwrap();
+        keyup_closure.forget();
+
         document
             .add_event_listener_with_callback("message", message_closure.as_ref().unchecked_ref())
             .unwrap();
         message_closure.forget();
+
+        window()
+            .add_event_listener_with_callback("blur", blur_closure.as_ref().unchecked_ref())
+            .unwrap();
+        blur_closure.forget();
     }
 
-    web_sys::console::log_1(&JsValue::from_serde(&the_board.clone().as_ref()).unwrap());
+    web_sys::console::log_1(&JsValue::from_str(
+        &serde_json::to_string(&*the_board.borrow()).unwrap(),
+    ));
+
+    let handle = GameHandle {
+        board: the_board.clone(),
+    };
 
     // setup the request_animation_frame closure
     *g.borrow_mut() = Some(Closure::wrap(Box::new(move |time: f64| {
         let mut board = the_board.borrow_mut();
-        if board.is_paused() || board.is_game_over(){
+        if view.is_paused(&board) || view.is_game_over(&board) {
             return;
         }
 
         board.process_input();
         board.update(time);
+        view.update(&mut board, time);
         draw_background(&context, time);
         draw_score(&context, board.score);
-        board.draw(&context);
+        draw_level(&context, board.level(), board.lines());
+        draw_time(&context, &board);
+        draw_puzzle(&context, &board);
+        view.draw(&board, &context);
         request_animation_frame(f.borrow().as_ref().unwrap());
     }) as Box<dyn FnMut(f64)>));
 
     request_animation_frame(g.borrow().as_ref().unwrap());
-    Ok(())
+    Ok(handle)
 }
 
 fn draw_score(context: &web_sys::CanvasRenderingContext2d, score: u32) {
+    context.set_fill_style(&JsValue::from_str("black"));
+    context.set_font("24px sans-serif");
+    context.fill_text(&format!("Score: {}", score), 10.0, 40.0).unwrap();
+}
 
+fn draw_level(context: &web_sys::CanvasRenderingContext2d, level: u32, lines: u32) {
     context.set_fill_style(&JsValue::from_str("black"));
     context.set_font("24px sans-serif");
-    context.fill_text(&format!("Score: {}", score.to_string()), 10.0, 40.0).unwrap();
+    context.fill_text(&format!("Level: {}", level), 10.0, 70.0).unwrap();
+    context.fill_text(&format!("Lines: {}", lines), 10.0, 100.0).unwrap();
+}
+
+/// Shows how long the game has been going, or how long is left in modes with a time limit
+fn draw_time(context: &web_sys::CanvasRenderingContext2d, board: &Board) {
+    let time = match board.rules().mode.time_limit() {
+        Some(limit) => limit - board.elapsed(),
+        None => board.elapsed(),
+    };
+
+    context.set_fill_style(&JsValue::from_str("black"));
+    context.set_font("24px sans-serif");
+    context
+        .fill_text(&format!("Time: {}", game_mode::format_time(time)), 10.0, 130.0)
+        .unwrap();
+}
+
+/// Shows the puzzle being played, its goal and how many of the ones before it were solved
+fn draw_puzzle(context: &web_sys::CanvasRenderingContext2d, board: &Board) {
+    let run = match board.puzzles() {
+        Some(run) => run,
+        None => return,
+    };
+    let solved = run.solved.iter().filter(|solved| **solved).count();
+
+    context.set_fill_style(&JsValue::from_str("black"));
+    context.set_font("24px sans-serif");
+    context
+        .fill_text(
+            &format!("Puzzle {}/{}: {}", run.current + 1, run.puzzles.len(), run.puzzle().name),
+            10.0,
+            160.0,
+        )
+        .unwrap();
+    context
+        .fill_text(&format!("Goal: {}", run.puzzle().goal), 10.0, 190.0)
+        .unwrap();
+    context
+        .fill_text(&format!("Solved: {}/{}", solved, run.solved.len()), 10.0, 220.0)
+        .unwrap();
 }
 
 pub fn draw_background(context: &web_sys::CanvasRenderingContext2d, time: f64) {
@@ -0,0 +1,124 @@
+//! The shapes of the built in pieces. Each orientation lists the cells of the piece relative to
+//! its origin, which is the cell the piece turns around.
+
+use crate::colors::{self, Style};
+use crate::pieces::piece_definition::PieceDefinition;
+use crate::rotation::KickTable;
+
+pub const LINE: PieceDefinition = PieceDefinition {
+    name: "Line",
+    orientations: &[
+        &[(-1, 0), (0, 0), (1, 0), (2, 0)],
+        &[(1, -1), (1, 0), (1, 1), (1, 2)],
+        &[(-1, 1), (0, 1), (1, 1), (2, 1)],
+        &[(0, -1), (0, 0), (0, 1), (0, 2)],
+    ],
+    style: Style {
+        stroke: colors::BAR_STROKE,
+        fill: colors::BAR_FILL,
+    },
+    kicks: KickTable::Line,
+    negative: false,
+};
+
+pub const SQUARE: PieceDefinition = PieceDefinition {
+    name: "Square",
+    orientations: &[&[(0, 0), (0, 1), (1, 0), (1, 1)]],
+    style: Style {
+        stroke: colors::SQUARE_STROKE,
+        fill: colors::SQUARE_FILL,
+    },
+    kicks: KickTable::InPlace,
+    negative: false,
+};
+
+pub const L_LEFT: PieceDefinition = PieceDefinition {
+    name: "LLeft",
+    orientations: &[
+        &[(-1, -1), (-1, 0), (0, 0), (1, 0)],
+        &[(1, -1), (0, -1), (0, 0), (0, 1)],
+        &[(-1, 0), (0, 0), (1, 0), (1, 1)],
+        &[(0, -1), (0, 0), (0, 1), (-1, 1)],
+    ],
+    style: Style {
+        stroke: colors::LPIECE_STROKE,
+        fill: colors::LPIECE_FILL,
+    },
+    kicks: KickTable::Standard,
+    negative: false,
+};
+
+pub const L_RIGHT: PieceDefinition = PieceDefinition {
+    name: "LRight",
+    orientations: &[
+        &[(1, -1), (1, 0), (0, 0), (-1, 0)],
+        &[(0, -1), (0, 0), (0, 1), (1, 1)],
+        &[(-1, 1), (-1, 0), (0, 0), (1, 0)],
+        &[(-1, -1), (0, -1), (0, 0), (0, 1)],
+    ],
+    style: Style {
+        stroke: colors::LPIECE_STROKE,
+        fill: colors::LPIECE_FILL,
+    },
+    kicks: KickTable::Standard,
+    negative: false,
+};
+
+pub const Z_LEFT: PieceDefinition = PieceDefinition {
+    name: "ZLeft",
+    orientations: &[
+        &[(-1, -1), (0, -1), (0, 0), (1, 0)],
+        &[(1, -1), (1, 0), (0, 0), (0, 1)],
+        &[(-1, 0), (0, 0), (0, 1), (1, 1)],
+        &[(0, -1), (0, 0), (-1, 0), (-1, 1)],
+    ],
+    style: Style {
+        stroke: colors::ZPIECE_STROKE,
+        fill: colors::ZPIECE_FILL,
+    },
+    kicks: KickTable::Standard,
+    negative: false,
+};
+
+pub const Z_RIGHT: PieceDefinition = PieceDefinition {
+    name: "ZRight",
+    orientations: &[
+        &[(1, -1), (0, -1), (0, 0), (-1, 0)],
+        &[(0, -1), (0, 0), (1, 0), (1, 1)],
+        &[(1, 0), (0, 0), (0, 1), (-1, 1)],
+        &[(-1, -1), (-1, 0), (0, 0), (0, 1)],
+    ],
+    style: Style {
+        stroke: colors::ZPIECE_STROKE,
+        fill: colors::ZPIECE_FILL,
+    },
+    kicks: KickTable::Standard,
+    negative: false,
+};
+
+pub const TRIANGLE: PieceDefinition = PieceDefinition {
+    name: "Triangle",
+    orientations: &[
+        &[(-1, 0), (0, 0), (1, 0), (0, -1)],
+        &[(0, -1), (0, 0), (0, 1), (1, 0)],
+        &[(-1, 0), (0, 0), (1, 0), (0, 1)],
+        &[(-1, 0), (0, 0), (0, -1), (0, 1)],
+    ],
+    style: Style {
+        stroke: colors::LPIECE_STROKE,
+        fill: colors::LPIECE_FILL,
+    },
+    kicks: KickTable::Standard,
+    negative: false,
+};
+
+pub const ERASER: PieceDefinition = PieceDefinition {
+    name: "Eraser",
+    orientations: &[&[(0, 0), (0, 1), (1, 0), (1, 1)]],
+    style: Style {
+        stroke: colors::ERASER_STROKE,
+        fill: colors::ERASER_FILL,
+    },
+    kicks: KickTable::InPlace,
+    negative: true,
+};
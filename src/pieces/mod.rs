@@ -1,17 +1,11 @@
+pub mod definitions;
 pub mod piece;
-pub mod line_piece;
-pub mod square_piece;
-pub mod l_piece_left;
-pub mod l_piece_right;
-pub mod z_piece_left;
-pub mod z_piece_right;
-pub mod triangle_piece;
+pub mod piece_definition;
+pub mod piece_kind;
+pub mod piece_set;
+pub mod polyomino;
 
-pub use l_piece_left::LPieceLeft;
-pub use l_piece_right::LPieceRight;
-pub use line_piece::LinePiece;
-pub use square_piece::SquarePiece;
 pub use piece::Piece;
-pub use z_piece_left::ZPieceLeft;
-pub use z_piece_right::ZPieceRight;
-pub use triangle_piece::TrianglePiece;
\ No newline at end of file
+pub use piece_definition::PieceDefinition;
+pub use piece_kind::PieceKind;
+pub use polyomino::Polyomino;
@@ -1,4 +1,5 @@
 use crate::geometry::{Position, Rect};
+use crate::pieces::PieceKind;
 use serde::Serialize;
 
 pub trait Piece: ClonePiece + std::fmt::Display {
@@ -6,10 +7,12 @@ pub trait Piece: ClonePiece + std::fmt::Display {
     fn mask(&self) -> Vec<Position<i32>>;
     fn rotate_left(&mut self);
     fn rotate_right(&mut self);
+    fn rotate_180(&mut self);
     fn move_left(&mut self);
     fn move_right(&mut self);
     fn set_origin(&mut self, x: i32, y: i32);
     fn get_origin(&self) -> Position<i32>;
+    fn kind(&self) -> PieceKind;
     fn draw(
         &self,
         context: &web_sys::CanvasRenderingContext2d,
@@ -25,7 +28,7 @@ pub trait ClonePiece {
 
 impl Clone for Box<dyn Piece> {
     fn clone(&self) -> Self {
-        return self.clone_piece();
+        self.clone_piece()
     }
 }
 
@@ -0,0 +1,16 @@
+use crate::colors::Style;
+use crate::rotation::KickTable;
+use serde::Serialize;
+
+/// PieceDefinition describes the shape of a piece as data, which is all a `Polyomino` needs to
+/// move, rotate and draw it
+#[derive(Serialize, Debug, PartialEq, Eq, Hash)]
+pub struct PieceDefinition {
+    pub name: &'static str,
+    // the cells of the piece relative to its origin, for each orientation in the order clockwise
+    // rotations go through them, starting with the one it spawns in
+    pub orientations: &'static [&'static [(i32, i32)]],
+    pub style: Style,
+    pub kicks: KickTable, // how a rotation that doesn't fit is moved around to make it fit
+    pub negative: bool,   // clears the cells it lands on instead of filling them
+}
@@ -0,0 +1,81 @@
+use crate::colors::Style;
+use crate::pieces::{definitions, Piece, PieceDefinition, Polyomino};
+use serde::Serialize;
+
+/// PieceKind names each of the pieces without carrying any position or orientation, which is what
+/// randomizers deal and what the board turns into a live piece when it spawns one.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PieceKind {
+    Line,
+    Square,
+    LLeft,
+    LRight,
+    ZLeft,
+    ZRight,
+    Triangle,
+    Eraser, // a negative piece, that clears the cells it lands on instead of filling them
+    Custom(&'static PieceDefinition), // a piece from a set loaded at runtime, see `piece_set`
+}
+
+impl PieceKind {
+    /// Every kind of piece that fills the board, in a fixed order; erasers are only dealt by
+    /// randomizers that ask for them
+    pub const ALL: [PieceKind; 7] = [
+        PieceKind::Line,
+        PieceKind::Square,
+        PieceKind::LLeft,
+        PieceKind::LRight,
+        PieceKind::ZLeft,
+        PieceKind::ZRight,
+        PieceKind::Triangle,
+    ];
+
+    /// The built in kind of piece with the given name, e.g. "Line" or "Eraser"
+    pub fn from_name(name: &str) -> Option<Self> {
+        PieceKind::ALL
+            .iter()
+            .chain(std::iter::once(&PieceKind::Eraser))
+            .copied()
+            .find(|kind| kind.definition().name == name)
+    }
+
+    /// The shape of pieces of this kind
+    pub const fn definition(self) -> &'static PieceDefinition {
+        match self {
+            PieceKind::Line => &definitions::LINE,
+            PieceKind::Square => &definitions::SQUARE,
+            PieceKind::LLeft => &definitions::L_LEFT,
+            PieceKind::LRight => &definitions::L_RIGHT,
+            PieceKind::ZLeft => &definitions::Z_LEFT,
+            PieceKind::ZRight => &definitions::Z_RIGHT,
+            PieceKind::Triangle => &definitions::TRIANGLE,
+            PieceKind::Eraser => &definitions::ERASER,
+            PieceKind::Custom(definition) => definition,
+        }
+    }
+
+    /// Creates a piece of this kind with its origin at (x, y), in the orientation it spawns in
+    pub fn create(self, x: i32, y: i32) -> Box<dyn Piece> {
+        Box::new(Polyomino::new(self, x, y))
+    }
+
+    /// How pieces of this kind are drawn
+    pub const fn style(self) -> Style {
+        self.definition().style
+    }
+
+    /// Negative pieces clear the cells they land on instead of filling them
+    pub fn is_negative(self) -> bool {
+        self.definition().negative
+    }
+}
+
+/// Kinds of pieces are written as their names, e.g. "Line"
+impl Serialize for PieceKind {
+    fn serialize<S>(&self, s: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        s.serialize_str(self.definition().name)
+    }
+}
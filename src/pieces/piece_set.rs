@@ -0,0 +1,222 @@
+//! Piece sets describe, in JSON, pieces that aren't built into the game and how often each of them
+//! is dealt, e.g. a tromino set:
+//!
+//! ```json
+//! [
+//!     { "name": "I3", "cells": [[-1, 0], [0, 0], [1, 0]], "stroke": "red", "fill": "darkred", "weight": 1 },
+//!     { "name": "L3", "cells": [[0, 0], [1, 0], [0, 1]], "stroke": "cyan", "fill": "teal", "weight": 2 }
+//! ]
+//! ```
+//!
+//! `cells` lists the cells of the piece in the orientation it spawns in, relative to the cell the
+//! piece turns around. The other orientations are found by turning those cells a quarter at a
+//! time, unless they are given in `orientations` (in clockwise order, starting with the spawn
+//! orientation). A piece can also be made `"negative"`, so that it erases what it lands on.
+
+use crate::colors::Style;
+use crate::pieces::{PieceDefinition, PieceKind};
+use crate::rotation::KickTable;
+use serde::Deserialize;
+
+/// The most cells a piece of a set can span in either direction, which is as much as the preview
+/// and hold panels have room for
+pub const MAX_PIECE_SIZE: i32 = 5;
+
+/// How one piece of a set is written in JSON
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PieceSetEntry {
+    name: String,
+    #[serde(default)]
+    cells: Vec<(i32, i32)>,
+    #[serde(default)]
+    orientations: Vec<Vec<(i32, i32)>>,
+    stroke: String,
+    fill: String,
+    weight: u32,
+    #[serde(default)]
+    negative: bool,
+}
+
+/// Reads a piece set from JSON and makes a kind of piece for each of its entries, paired with how
+/// often it should be dealt. Returns a message saying what's wrong if the set isn't valid.
+///
+/// The definitions of the pieces live for as long as the game does, so every set that's loaded
+/// takes up a little memory for good.
+pub fn parse(json: &str) -> Result<Vec<(PieceKind, u32)>, String> {
+    let entries: Vec<PieceSetEntry> =
+        serde_json::from_str(json).map_err(|error| format!("invalid piece set: {}", error))?;
+
+    if entries.is_empty() {
+        return Err("a piece set needs at least one piece".to_string());
+    }
+    let total = entries
+        .iter()
+        .try_fold(0u32, |total, entry| total.checked_add(entry.weight))
+        .ok_or_else(|| format!("the weights of the set add up to more than {}", u32::MAX))?;
+    if total == 0 {
+        return Err("at least one piece of the set needs a weight above 0".to_string());
+    }
+
+    let mut pieces = Vec::with_capacity(entries.len());
+    for (i, entry) in entries.into_iter().enumerate() {
+        if pieces
+            .iter()
+            .any(|(kind, _): &(PieceKind, u32)| kind.definition().name == entry.name)
+        {
+            return Err(format!(
+                "there is more than one piece named {:?}",
+                entry.name
+            ));
+        }
+
+        let weight = entry.weight;
+        let definition = definition(entry).map_err(|error| format!("piece {}: {}", i, error))?;
+        pieces.push((PieceKind::Custom(definition), weight));
+    }
+
+    Ok(pieces)
+}
+
+/// Checks one entry of a set and turns it into the definition of a piece
+fn definition(entry: PieceSetEntry) -> Result<&'static PieceDefinition, String> {
+    let PieceSetEntry {
+        name,
+        cells,
+        orientations,
+        stroke,
+        fill,
+        negative,
+        ..
+    } = entry;
+
+    if name.is_empty() {
+        return Err("the name can't be empty".to_string());
+    }
+    if stroke.is_empty() || fill.is_empty() {
+        return Err(format!("{:?} needs both a stroke and a fill colour", name));
+    }
+    // checked before anything is worked out from the cells, so that none of it can overflow
+    let in_range = |n: &i32| (-MAX_PIECE_SIZE..=MAX_PIECE_SIZE).contains(n);
+    if let Some(cell) = cells
+        .iter()
+        .chain(orientations.iter().flatten())
+        .find(|(x, y)| !in_range(x) || !in_range(y))
+    {
+        return Err(format!(
+            "{:?} has cell {:?}, but no cell can be more than {} cells from the one it turns around",
+            name, cell, MAX_PIECE_SIZE
+        ));
+    }
+
+    let orientations = match (cells.is_empty(), orientations.is_empty()) {
+        (false, true) => turns(&cells),
+        (true, false) => orientations,
+        (false, false) => {
+            return Err(format!(
+                "{:?} has both cells and orientations, it needs only one of them",
+                name
+            ))
+        }
+        (true, true) => return Err(format!("{:?} has no cells", name)),
+    };
+
+    for (i, cells) in orientations.iter().enumerate() {
+        check_shape(cells, orientations[0].len())
+            .map_err(|error| format!("orientation {} of {:?} {}", i, name, error))?;
+    }
+    if orientations[0].iter().any(|(_, y)| *y < -1) {
+        return Err(format!(
+            "{:?} can't spawn inside the board: no cell can be more than 1 row above the one it \
+             turns around",
+            name
+        ));
+    }
+
+    let orientations: Vec<&'static [(i32, i32)]> = orientations
+        .into_iter()
+        .map(|cells| &*Box::leak(cells.into_boxed_slice()))
+        .collect();
+
+    Ok(Box::leak(Box::new(PieceDefinition {
+        name: Box::leak(name.into_boxed_str()),
+        orientations: Box::leak(orientations.into_boxed_slice()),
+        style: Style {
+            stroke: Box::leak(stroke.into_boxed_str()),
+            fill: Box::leak(fill.into_boxed_str()),
+        },
+        kicks: KickTable::Standard,
+        negative,
+    })))
+}
+
+/// Makes sure the cells of an orientation form a single piece that isn't too big
+fn check_shape(cells: &[(i32, i32)], expected_cells: usize) -> Result<(), String> {
+    if cells.is_empty() {
+        return Err("has no cells".to_string());
+    }
+    if cells.len() != expected_cells {
+        return Err(format!(
+            "has {} cells, but the first one has {}",
+            cells.len(),
+            expected_cells
+        ));
+    }
+
+    for (i, cell) in cells.iter().enumerate() {
+        if cells[..i].contains(cell) {
+            return Err(format!("has cell {:?} more than once", cell));
+        }
+    }
+
+    let width =
+        cells.iter().map(|(x, _)| x).max().unwrap() - cells.iter().map(|(x, _)| x).min().unwrap();
+    let height =
+        cells.iter().map(|(_, y)| y).max().unwrap() - cells.iter().map(|(_, y)| y).min().unwrap();
+    if width >= MAX_PIECE_SIZE || height >= MAX_PIECE_SIZE {
+        return Err(format!(
+            "is more than {} cells wide or tall",
+            MAX_PIECE_SIZE
+        ));
+    }
+
+    // every cell has to be reachable from the first one through cells that share a side
+    let mut reached = vec![cells[0]];
+    let mut i = 0;
+    while i < reached.len() {
+        let (x, y) = reached[i];
+        for neighbour in [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)].iter() {
+            if cells.contains(neighbour) && !reached.contains(neighbour) {
+                reached.push(*neighbour);
+            }
+        }
+        i += 1;
+    }
+    if reached.len() != cells.len() {
+        return Err("has cells that aren't connected to the rest".to_string());
+    }
+
+    Ok(())
+}
+
+/// The orientations of a piece, found by turning its spawn orientation clockwise a quarter at a
+/// time until it looks like one of the orientations before it
+fn turns(cells: &[(i32, i32)]) -> Vec<Vec<(i32, i32)>> {
+    let mut orientations = vec![cells.to_vec()];
+
+    loop {
+        let last = orientations.last().unwrap();
+        // with y pointing down a clockwise quarter turn takes (x, y) to (-y, x)
+        let turned: Vec<(i32, i32)> = last.iter().map(|(x, y)| (-y, *x)).collect();
+
+        let seen = orientations.iter().any(|orientation| {
+            orientation.len() == turned.len()
+                && turned.iter().all(|cell| orientation.contains(cell))
+        });
+        if seen {
+            return orientations;
+        }
+
+        orientations.push(turned);
+    }
+}
@@ -0,0 +1,190 @@
+use crate::geometry::{Position, Rect};
+use crate::pieces::piece::ClonePiece;
+use crate::pieces::piece::Piece;
+use crate::pieces::PieceKind;
+use serde::Serialize;
+use std::fmt::Display;
+
+/// Polyomino is a piece made of the cells its kind's `PieceDefinition` lists, so every shape moves,
+/// rotates and draws the same way
+#[derive(Serialize, Copy, Clone)]
+pub struct Polyomino {
+    kind: PieceKind,
+    orientation: usize, // index into the orientations of the definition
+    origin: Position<i32>,
+}
+
+impl Polyomino {
+    pub fn new(kind: PieceKind, x: i32, y: i32) -> Self {
+        Polyomino {
+            kind,
+            orientation: 0,
+            origin: Position { x, y },
+        }
+    }
+
+    /// The cells of the piece in its current orientation, relative to its origin
+    fn cells(&self) -> &'static [(i32, i32)] {
+        self.kind.definition().orientations[self.orientation]
+    }
+}
+
+impl ClonePiece for Polyomino {
+    fn clone_piece(&self) -> Box<dyn Piece> {
+        Box::new(*self)
+    }
+}
+
+impl Display for Polyomino {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        f.write_str(&serde_json::to_string(self).expect("could not convert to json"))
+    }
+}
+
+impl Piece for Polyomino {
+    fn bounding_box(&self) -> Rect<i32> {
+        let cells = self.cells();
+
+        Rect {
+            x1: self.origin.x + cells.iter().map(|(x, _)| *x).min().unwrap_or(0),
+            y1: self.origin.y + cells.iter().map(|(_, y)| *y).min().unwrap_or(0),
+            x2: self.origin.x + cells.iter().map(|(x, _)| *x).max().unwrap_or(0),
+            y2: self.origin.y + cells.iter().map(|(_, y)| *y).max().unwrap_or(0),
+        }
+    }
+
+    fn mask(&self) -> Vec<Position<i32>> {
+        self.cells().iter().map(|cell| self.origin + *cell).collect()
+    }
+
+    fn rotate_left(&mut self) {
+        let orientations = self.kind.definition().orientations.len();
+        self.orientation = (self.orientation + orientations - 1) % orientations;
+    }
+
+    fn rotate_right(&mut self) {
+        let orientations = self.kind.definition().orientations.len();
+        self.orientation = (self.orientation + 1) % orientations;
+    }
+
+    fn rotate_180(&mut self) {
+        let orientations = self.kind.definition().orientations.len();
+        self.orientation = (self.orientation + 2) % orientations;
+    }
+
+    fn move_left(&mut self) {
+        self.origin.x -= 1;
+    }
+
+    fn move_right(&mut self) {
+        self.origin.x += 1;
+    }
+
+    fn set_origin(&mut self, x: i32, y: i32) {
+        self.origin.x = x;
+        self.origin.y = y;
+    }
+
+    fn get_origin(&self) -> Position<i32> {
+        self.origin
+    }
+
+    fn kind(&self) -> PieceKind {
+        self.kind
+    }
+
+    fn draw(
+        &self,
+        context: &web_sys::CanvasRenderingContext2d,
+        origin_x: f64,
+        origin_y: f64,
+        pixels_per_cell: f64,
+    ) {
+        context
+            .set_line_dash(&js_sys::Array::new())
+            .unwrap();
+
+        let style = self.kind.style();
+        context.set_stroke_style(&style.stroke.into());
+        context.set_fill_style(&style.fill.into());
+
+        let point = |(x, y): (i32, i32)| {
+            (
+                origin_x + x as f64 * pixels_per_cell,
+                origin_y + y as f64 * pixels_per_cell,
+            )
+        };
+
+        context.begin_path();
+
+        for corners in outline(self.cells()) {
+            let (x, y) = point(corners[0]);
+            context.move_to(x, y);
+            for corner in &corners[1..] {
+                let (x, y) = point(*corner);
+                context.line_to(x, y);
+            }
+            context.close_path();
+        }
+
+        context.stroke();
+        context.fill();
+
+        // cross out every cell of a negative piece, so it can't be mistaken for one that fills the
+        // board
+        if self.kind.is_negative() {
+            context.begin_path();
+            for (x, y) in self.cells() {
+                let (x1, y1) = point((*x, *y));
+                let (x2, y2) = point((x + 1, y + 1));
+                context.move_to(x1, y1);
+                context.line_to(x2, y2);
+                context.move_to(x2, y1);
+                context.line_to(x1, y2);
+            }
+            context.stroke();
+        }
+    }
+}
+
+/// The outline of a group of cells, as closed loops of the corners it goes through
+fn outline(cells: &[(i32, i32)]) -> Vec<Vec<(i32, i32)>> {
+    // every cell adds its four sides, going clockwise; a side shared by two cells is added once in
+    // each direction, so the two cancel out and only the sides on the outside are left
+    let mut sides: Vec<((i32, i32), (i32, i32))> = Vec::with_capacity(cells.len() * 4);
+    for (x, y) in cells {
+        let (x, y) = (*x, *y);
+        let cell_sides = [
+            ((x, y), (x + 1, y)),
+            ((x + 1, y), (x + 1, y + 1)),
+            ((x + 1, y + 1), (x, y + 1)),
+            ((x, y + 1), (x, y)),
+        ];
+
+        for (from, to) in cell_sides.iter() {
+            match sides.iter().position(|side| *side == (*to, *from)) {
+                Some(shared) => {
+                    sides.swap_remove(shared);
+                }
+                None => sides.push((*from, *to)),
+            }
+        }
+    }
+
+    // walk the sides that are left from one end to the next until each loop closes
+    let mut loops = Vec::with_capacity(1);
+    while let Some((start, mut next)) = sides.pop() {
+        let mut corners = vec![start];
+        while next != start {
+            corners.push(next);
+            let following = sides
+                .iter()
+                .position(|(from, _)| *from == next)
+                .expect("the outline of a group of cells is always closed");
+            next = sides.swap_remove(following).1;
+        }
+        loops.push(corners);
+    }
+
+    loops
+}
@@ -0,0 +1,289 @@
+//! Puzzles start the board with some cells already filled, deal a fixed list of pieces and set a
+//! goal to reach with them. They are written either in JSON:
+//!
+//! ```json
+//! [
+//!     {
+//!         "name": "First Tetris",
+//!         "goal": "4 lines",
+//!         "pieces": ["Line"],
+//!         "board": ["#########.", "#########.", "#########.", "#########."]
+//!     }
+//! ]
+//! ```
+//!
+//! or as text, one puzzle after another with blank lines between them:
+//!
+//! ```text
+//! name: First Tetris
+//! goal: 4 lines
+//! pieces: Line
+//! #########.
+//! #########.
+//! #########.
+//! #########.
+//! ```
+//!
+//! The board rows are the bottom rows of the board, top to bottom, with `#` for a filled cell and
+//! `.` for an empty one. The goal is a number of lines (`"2 lines"`), `"perfect clear"` or the name
+//! of a clear, e.g. `"T-Spin Double"`, and isn't case sensitive. The pieces are named as in
+//! `PieceKind::from_name`.
+
+use crate::pieces::PieceKind;
+use crate::scoring::ClearType;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// What has to be done to solve a puzzle
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Goal {
+    Lines(u32),       // clear at least this many lines
+    PerfectClear,     // clear lines so that the board is left empty
+    Clear(ClearType), // make a clear of this type, e.g. a T-Spin Double
+}
+
+impl Goal {
+    /// Reads a goal written as "4 lines", "perfect clear" or the name of a clear type
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim().to_lowercase();
+        if text == "perfect clear" {
+            return Ok(Goal::PerfectClear);
+        }
+        if let Some(clear_type) = ClearType::ALL
+            .iter()
+            .find(|clear_type| clear_type.name().to_lowercase() == text)
+        {
+            return Ok(Goal::Clear(*clear_type));
+        }
+
+        let mut words = text.split_whitespace();
+        match (words.next(), words.next(), words.next()) {
+            (Some(count), Some("line"), None) | (Some(count), Some("lines"), None) => {
+                match count.parse::<u32>() {
+                    Ok(lines) if lines > 0 => Ok(Goal::Lines(lines)),
+                    _ => Err(format!("{:?} isn't a number of lines above 0", count)),
+                }
+            }
+            _ => Err(format!(
+                "{:?} isn't a goal, try e.g. \"2 lines\", \"perfect clear\" or \"T-Spin Double\"",
+                text
+            )),
+        }
+    }
+}
+
+/// Goals are written the way `Goal::parse` reads them, e.g. "4 Lines" or "T-Spin Double"
+impl fmt::Display for Goal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Goal::Lines(1) => write!(f, "1 Line"),
+            Goal::Lines(lines) => write!(f, "{} Lines", lines),
+            Goal::PerfectClear => write!(f, "Perfect Clear"),
+            Goal::Clear(clear_type) => write!(f, "{}", clear_type.name()),
+        }
+    }
+}
+
+impl Serialize for Goal {
+    fn serialize<S>(&self, s: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        s.collect_str(self)
+    }
+}
+
+/// Puzzle is one board to solve, see the module docs for how it's written
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Puzzle {
+    pub name: String,
+    pub goal: Goal,
+    pub pieces: Vec<PieceKind>, // dealt in this order, and no others
+    pub board: Vec<Vec<bool>>,  // the bottom rows of the board, top to bottom, true where filled
+}
+
+impl Puzzle {
+    /// Checks the parts of a puzzle as they're written and puts them together
+    fn new(name: &str, goal: &str, pieces: &[&str], board: &[&str]) -> Result<Self, String> {
+        let goal = Goal::parse(goal)?;
+
+        if pieces.is_empty() {
+            return Err("a puzzle needs at least one piece".to_string());
+        }
+        let pieces = pieces
+            .iter()
+            .map(|name| {
+                PieceKind::from_name(name)
+                    .ok_or_else(|| format!("there is no piece named {:?}", name))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let board = board
+            .iter()
+            .enumerate()
+            .map(|(y, row)| {
+                let cells = row
+                    .chars()
+                    .map(|c| match c {
+                        '#' => Ok(true),
+                        '.' => Ok(false),
+                        _ => Err(format!(
+                            "row {} has a {:?}, cells are either '#' or '.'",
+                            y, c
+                        )),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                if !cells.is_empty() && cells.iter().all(|filled| *filled) {
+                    return Err(format!("row {} is already complete", y));
+                }
+
+                Ok(cells)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if board.iter().any(|row| row.len() != board[0].len()) {
+            return Err("the rows of the board aren't all the same width".to_string());
+        }
+
+        Ok(Puzzle {
+            name: name.to_string(),
+            goal,
+            pieces,
+            board,
+        })
+    }
+
+    /// How many columns the board of the puzzle has, 0 when it starts empty
+    pub fn width(&self) -> usize {
+        self.board.first().map_or(0, Vec::len)
+    }
+}
+
+/// How one puzzle is written in JSON
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PuzzleEntry {
+    name: String,
+    goal: String,
+    pieces: Vec<String>,
+    #[serde(default)]
+    board: Vec<String>,
+}
+
+/// Reads a list of puzzles written in JSON, if it starts with '[', or as text otherwise. Returns a
+/// message saying what's wrong if any of them isn't valid.
+pub fn parse(source: &str) -> Result<Vec<Puzzle>, String> {
+    let puzzles = if source.trim_start().starts_with('[') {
+        parse_json(source)?
+    } else {
+        parse_text(source)?
+    };
+
+    if puzzles.is_empty() {
+        return Err("there are no puzzles".to_string());
+    }
+
+    Ok(puzzles)
+}
+
+fn parse_json(json: &str) -> Result<Vec<Puzzle>, String> {
+    let entries: Vec<PuzzleEntry> =
+        serde_json::from_str(json).map_err(|error| format!("invalid puzzles: {}", error))?;
+
+    entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            let pieces: Vec<&str> = entry.pieces.iter().map(String::as_str).collect();
+            let board: Vec<&str> = entry.board.iter().map(String::as_str).collect();
+            Puzzle::new(&entry.name, &entry.goal, &pieces, &board)
+                .map_err(|error| format!("puzzle {}: {}", i, error))
+        })
+        .collect()
+}
+
+fn parse_text(text: &str) -> Result<Vec<Puzzle>, String> {
+    let mut puzzles = Vec::new();
+    let lines: Vec<&str> = text.lines().map(str::trim).collect();
+
+    for (i, block) in lines
+        .split(|line| line.is_empty())
+        .filter(|block| !block.is_empty())
+        .enumerate()
+    {
+        let puzzle = parse_text_block(block).map_err(|error| format!("puzzle {}: {}", i, error))?;
+        puzzles.push(puzzle);
+    }
+
+    Ok(puzzles)
+}
+
+/// Reads one puzzle written as text: the `name:`, `goal:` and `pieces:` lines, then the board
+fn parse_text_block(block: &[&str]) -> Result<Puzzle, String> {
+    let (mut name, mut goal, mut pieces) = (None, None, None);
+    let mut board = Vec::new();
+
+    for line in block {
+        match line.split_once(':') {
+            Some((key, value)) if board.is_empty() => {
+                let value = value.trim();
+                match key.trim() {
+                    "name" => name = Some(value),
+                    "goal" => goal = Some(value),
+                    "pieces" => pieces = Some(value.split_whitespace().collect::<Vec<_>>()),
+                    key => return Err(format!("{:?} isn't part of a puzzle", key)),
+                }
+            }
+            _ => board.push(*line),
+        }
+    }
+
+    let missing = |field: &str| format!("the puzzle has no {:?} line", field);
+    Puzzle::new(
+        name.ok_or_else(|| missing("name"))?,
+        goal.ok_or_else(|| missing("goal"))?,
+        &pieces.ok_or_else(|| missing("pieces"))?,
+        &board,
+    )
+}
+
+/// PuzzleRun follows a game of puzzles: which one is being played, how the ones before it went
+/// and what has been done towards the goal of the current one
+#[derive(Serialize, Clone, Debug)]
+pub struct PuzzleRun {
+    pub puzzles: Vec<Puzzle>,
+    pub current: usize,              // the index of the puzzle being played
+    pub solved: Vec<bool>,           // whether each of the finished puzzles was solved, in order
+    pub lines: u32,                  // lines cleared in the current puzzle
+    pub clear_types: Vec<ClearType>, // the clears made in the current puzzle
+}
+
+impl PuzzleRun {
+    pub fn new(puzzles: Vec<Puzzle>) -> Self {
+        PuzzleRun {
+            puzzles,
+            current: 0,
+            solved: Vec::new(),
+            lines: 0,
+            clear_types: Vec::new(),
+        }
+    }
+
+    pub fn puzzle(&self) -> &Puzzle {
+        &self.puzzles[self.current]
+    }
+
+    /// Has the goal of the current puzzle been reached, given whether the board is now empty
+    pub fn is_goal_met(&self, is_board_empty: bool) -> bool {
+        match self.puzzle().goal {
+            Goal::Lines(lines) => self.lines >= lines,
+            Goal::PerfectClear => self.lines > 0 && is_board_empty,
+            Goal::Clear(clear_type) => self.clear_types.contains(&clear_type),
+        }
+    }
+
+    /// Starts the current puzzle over, forgetting what was done towards its goal
+    pub fn reset(&mut self) {
+        self.lines = 0;
+        self.clear_types.clear();
+    }
+}
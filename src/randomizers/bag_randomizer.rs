@@ -0,0 +1,45 @@
+use crate::pieces::PieceKind;
+use crate::randomizers::Randomizer;
+use rand::seq::SliceRandom;
+use rand::RngCore;
+use serde::Serialize;
+use std::fmt::Display;
+
+/// BagRandomizer puts `copies` of every piece into a bag, shuffles it and deals the whole bag
+/// before refilling it. With one copy (the 7-bag) the same piece is never dealt more than twice in
+/// a row and no piece is missing for more than 12 turns.
+#[derive(Serialize, Clone)]
+pub struct BagRandomizer {
+    copies: usize,
+    bag: Vec<PieceKind>, // pieces left in the current bag, dealt from the back
+}
+
+impl BagRandomizer {
+    pub fn new(copies: usize) -> Self {
+        assert!(copies > 0, "a bag needs at least one copy of each piece");
+
+        BagRandomizer {
+            copies,
+            bag: Vec::with_capacity(copies * PieceKind::ALL.len()),
+        }
+    }
+}
+
+impl Display for BagRandomizer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        f.write_str(&serde_json::to_string(self).expect("could not convert to json"))
+    }
+}
+
+impl Randomizer for BagRandomizer {
+    fn next(&mut self, rng: &mut dyn RngCore) -> PieceKind {
+        if self.bag.is_empty() {
+            for _ in 0..self.copies {
+                self.bag.extend_from_slice(&PieceKind::ALL);
+            }
+            self.bag.shuffle(rng);
+        }
+
+        self.bag.pop().expect("the bag was just refilled")
+    }
+}
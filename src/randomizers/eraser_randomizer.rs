@@ -0,0 +1,37 @@
+use crate::pieces::PieceKind;
+use crate::randomizers::Randomizer;
+use rand::{Rng, RngCore};
+use serde::Serialize;
+use std::fmt::Display;
+
+/// EraserRandomizer mixes eraser pieces into what another randomizer deals: each piece has a fixed
+/// chance of being an eraser, otherwise it's whatever the other randomizer deals next
+#[derive(Serialize, Clone)]
+pub struct EraserRandomizer {
+    pieces: Box<dyn Randomizer>, // deals everything that isn't an eraser
+    percent: u32,                // the chance of each piece being an eraser, out of 100
+}
+
+impl EraserRandomizer {
+    pub fn new(pieces: Box<dyn Randomizer>, percent: u32) -> Self {
+        assert!(percent <= 100, "the eraser chance can't be above 100%");
+
+        EraserRandomizer { pieces, percent }
+    }
+}
+
+impl Display for EraserRandomizer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        f.write_str(&serde_json::to_string(self).expect("could not convert to json"))
+    }
+}
+
+impl Randomizer for EraserRandomizer {
+    fn next(&mut self, rng: &mut dyn RngCore) -> PieceKind {
+        if rng.gen_range(0, 100) < self.percent {
+            return PieceKind::Eraser;
+        }
+
+        self.pieces.next(rng)
+    }
+}
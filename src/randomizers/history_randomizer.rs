@@ -0,0 +1,77 @@
+use crate::pieces::PieceKind;
+use crate::randomizers::Randomizer;
+use rand::{Rng, RngCore};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fmt::Display;
+
+/// HistoryRandomizer is the randomizer of the TGM games: it remembers the last few pieces it dealt
+/// and rolls again, up to `rerolls` times, whenever it picks one of them.
+#[derive(Serialize, Clone)]
+pub struct HistoryRandomizer {
+    rerolls: u32,
+    history: VecDeque<PieceKind>, // the most recently dealt piece is at the back
+    is_first: bool,
+}
+
+impl HistoryRandomizer {
+    /// `history` is what the randomizer pretends to have dealt before the first piece
+    pub fn new(history: Vec<PieceKind>, rerolls: u32) -> Self {
+        HistoryRandomizer {
+            rerolls,
+            history: history.into(),
+            is_first: true,
+        }
+    }
+
+    /// A history of 4 pieces seeded with Z pieces and 4 rerolls, like the first TGM
+    pub fn tgm() -> Self {
+        HistoryRandomizer::new(
+            vec![
+                PieceKind::ZLeft,
+                PieceKind::ZRight,
+                PieceKind::ZLeft,
+                PieceKind::ZRight,
+            ],
+            4,
+        )
+    }
+
+    fn roll(rng: &mut dyn RngCore) -> PieceKind {
+        PieceKind::ALL[rng.gen_range(0, PieceKind::ALL.len() as u32) as usize]
+    }
+}
+
+impl Display for HistoryRandomizer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        f.write_str(&serde_json::to_string(self).expect("could not convert to json"))
+    }
+}
+
+impl Randomizer for HistoryRandomizer {
+    fn next(&mut self, rng: &mut dyn RngCore) -> PieceKind {
+        let mut next = HistoryRandomizer::roll(rng);
+
+        if self.is_first {
+            // the first piece is never one that can only be placed by leaving a hole
+            self.is_first = false;
+            while let PieceKind::Square | PieceKind::ZLeft | PieceKind::ZRight = next {
+                next = HistoryRandomizer::roll(rng);
+            }
+        } else {
+            for _ in 0..self.rerolls {
+                if !self.history.contains(&next) {
+                    break;
+                }
+                next = HistoryRandomizer::roll(rng);
+            }
+        }
+
+        if !self.history.is_empty() {
+            self.history.pop_front();
+            self.history.push_back(next);
+        }
+
+        next
+    }
+}
@@ -0,0 +1,13 @@
+mod bag_randomizer;
+mod eraser_randomizer;
+mod history_randomizer;
+mod randomizer;
+mod uniform_randomizer;
+mod weighted_randomizer;
+
+pub use bag_randomizer::BagRandomizer;
+pub use eraser_randomizer::EraserRandomizer;
+pub use history_randomizer::HistoryRandomizer;
+pub use randomizer::{from_name, Randomizer};
+pub use uniform_randomizer::UniformRandomizer;
+pub use weighted_randomizer::WeightedRandomizer;
@@ -0,0 +1,57 @@
+use crate::pieces::PieceKind;
+use crate::randomizers::{
+    BagRandomizer, EraserRandomizer, HistoryRandomizer, UniformRandomizer, WeightedRandomizer,
+};
+use rand::RngCore;
+use serde::Serialize;
+
+/// A Randomizer decides which kind of piece is dealt next.
+///
+/// Randomizers don't own a random number generator: they draw from the one that is passed in, which
+/// is the board's seeded generator, so that a game can be replayed from its seed.
+pub trait Randomizer: CloneRandomizer + std::fmt::Display {
+    fn next(&mut self, rng: &mut dyn RngCore) -> PieceKind;
+}
+
+/// Lets the board copy its randomizer, with whatever it has dealt so far, when it takes a snapshot
+pub trait CloneRandomizer {
+    fn clone_randomizer(&self) -> Box<dyn Randomizer>;
+}
+
+impl<T: Randomizer + Clone + 'static> CloneRandomizer for T {
+    fn clone_randomizer(&self) -> Box<dyn Randomizer> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn Randomizer> {
+    fn clone(&self) -> Self {
+        self.clone_randomizer()
+    }
+}
+
+impl Serialize for Box<dyn Randomizer> {
+    fn serialize<S>(&self, s: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        s.collect_str(self)
+    }
+}
+
+/// Creates one of the built in randomizers by name: "7-bag", "14-bag", "history", "weighted",
+/// "uniform" or "rescue", a 7-bag with a 1 in 10 chance of each piece being an eraser
+pub fn from_name(name: &str) -> Option<Box<dyn Randomizer>> {
+    match name {
+        "7-bag" => Some(Box::new(BagRandomizer::new(1))),
+        "14-bag" => Some(Box::new(BagRandomizer::new(2))),
+        "history" => Some(Box::new(HistoryRandomizer::tgm())),
+        "weighted" => Some(Box::new(WeightedRandomizer::classic())),
+        "uniform" => Some(Box::new(UniformRandomizer::new())),
+        "rescue" => Some(Box::new(EraserRandomizer::new(
+            Box::new(BagRandomizer::new(1)),
+            10,
+        ))),
+        _ => None,
+    }
+}
@@ -0,0 +1,27 @@
+use crate::pieces::PieceKind;
+use crate::randomizers::Randomizer;
+use rand::{Rng, RngCore};
+use serde::Serialize;
+use std::fmt::Display;
+
+/// UniformRandomizer deals every piece with the same chance and no memory of what came before
+#[derive(Serialize, Clone, Default)]
+pub struct UniformRandomizer {}
+
+impl UniformRandomizer {
+    pub fn new() -> Self {
+        UniformRandomizer {}
+    }
+}
+
+impl Display for UniformRandomizer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        f.write_str(&serde_json::to_string(self).expect("could not convert to json"))
+    }
+}
+
+impl Randomizer for UniformRandomizer {
+    fn next(&mut self, rng: &mut dyn RngCore) -> PieceKind {
+        PieceKind::ALL[rng.gen_range(0, PieceKind::ALL.len() as u32) as usize]
+    }
+}
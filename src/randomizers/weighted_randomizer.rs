@@ -0,0 +1,55 @@
+use crate::pieces::PieceKind;
+use crate::randomizers::Randomizer;
+use rand::{Rng, RngCore};
+use serde::Serialize;
+use std::fmt::Display;
+
+/// WeightedRandomizer deals each piece independently, with a chance proportional to its weight
+#[derive(Serialize, Clone)]
+pub struct WeightedRandomizer {
+    weights: Vec<(PieceKind, u32)>,
+    total: u32,
+}
+
+impl WeightedRandomizer {
+    pub fn new(weights: Vec<(PieceKind, u32)>) -> Self {
+        let total = weights.iter().map(|(_, weight)| weight).sum();
+        assert!(total > 0, "at least one piece needs a weight above 0");
+
+        WeightedRandomizer { weights, total }
+    }
+
+    /// The odds the game originally used: roughly even, with a little less for the triangle
+    pub fn classic() -> Self {
+        WeightedRandomizer::new(vec![
+            (PieceKind::LRight, 51),
+            (PieceKind::LLeft, 50),
+            (PieceKind::Square, 50),
+            (PieceKind::Line, 50),
+            (PieceKind::ZLeft, 50),
+            (PieceKind::ZRight, 50),
+            (PieceKind::Triangle, 49),
+        ])
+    }
+}
+
+impl Display for WeightedRandomizer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        f.write_str(&serde_json::to_string(self).expect("could not convert to json"))
+    }
+}
+
+impl Randomizer for WeightedRandomizer {
+    fn next(&mut self, rng: &mut dyn RngCore) -> PieceKind {
+        let mut roll = rng.gen_range(0, self.total);
+
+        for (kind, weight) in &self.weights {
+            if roll < *weight {
+                return *kind;
+            }
+            roll -= weight;
+        }
+
+        unreachable!("the roll is always below the total of the weights")
+    }
+}
@@ -0,0 +1,145 @@
+use crate::pieces::PieceKind;
+use serde::Serialize;
+
+/// The four rotation states of the Super Rotation System, relative to the orientation a piece
+/// spawns in (also known as 0, R, 2 and L)
+#[derive(Serialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RotationState {
+    Spawn,
+    Right,
+    Reverse,
+    Left,
+}
+
+impl RotationState {
+    /// The state after a clockwise quarter turn
+    pub fn rotated_right(self) -> Self {
+        match self {
+            RotationState::Spawn => RotationState::Right,
+            RotationState::Right => RotationState::Reverse,
+            RotationState::Reverse => RotationState::Left,
+            RotationState::Left => RotationState::Spawn,
+        }
+    }
+
+    /// The state after a counter-clockwise quarter turn
+    pub fn rotated_left(self) -> Self {
+        match self {
+            RotationState::Spawn => RotationState::Left,
+            RotationState::Left => RotationState::Reverse,
+            RotationState::Reverse => RotationState::Right,
+            RotationState::Right => RotationState::Spawn,
+        }
+    }
+
+    /// The state after a half turn
+    pub fn rotated_180(self) -> Self {
+        self.rotated_right().rotated_right()
+    }
+}
+
+/// KickTable picks the offsets a piece is tried at when its rotation doesn't fit in place
+#[derive(Serialize, Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum KickTable {
+    Standard, // the SRS table shared by most pieces
+    Line,     // the SRS table of the line piece
+    InPlace,  // the piece only rotates where it is
+}
+
+/// Kick describes how a rotation was made to fit: which of the tests in the kick table succeeded
+/// (0 means the piece rotated in place) and by how much the piece was moved, in board coordinates
+#[derive(Serialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Kick {
+    pub test: usize,
+    pub x: i32,
+    pub y: i32,
+    pub half_turn: bool, // the rotation was a 180, whose kicks come from a table of their own
+}
+
+// The tables below are written the way SRS is usually documented, with y pointing up. `kicks`
+// flips them to the board, where y points down.
+
+const JLSTZ_KICKS: [[(i32, i32); 5]; 8] = [
+    [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)], // 0 -> R
+    [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],     // R -> 0
+    [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],     // R -> 2
+    [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)], // 2 -> R
+    [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],    // 2 -> L
+    [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],  // L -> 2
+    [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],  // L -> 0
+    [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],    // 0 -> L
+];
+
+const LINE_KICKS: [[(i32, i32); 5]; 8] = [
+    [(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)], // 0 -> R
+    [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)], // R -> 0
+    [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)], // R -> 2
+    [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)], // 2 -> R
+    [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)], // 2 -> L
+    [(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)], // L -> 2
+    [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)], // L -> 0
+    [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)], // 0 -> L
+];
+
+// SRS has no half turns, so these follow the 180 kicks games built on it commonly use: straight
+// up or down first, then to the sides.
+
+const JLSTZ_180_KICKS: [&[(i32, i32)]; 4] = [
+    &[(0, 0), (0, 1), (1, 1), (-1, 1), (1, 0), (-1, 0)], // 0 -> 2
+    &[(0, 0), (1, 0), (1, 2), (1, 1), (0, 2), (0, 1)],   // R -> L
+    &[(0, 0), (0, -1), (-1, -1), (1, -1), (-1, 0), (1, 0)], // 2 -> 0
+    &[(0, 0), (-1, 0), (-1, 2), (-1, 1), (0, 2), (0, 1)], // L -> R
+];
+
+const LINE_180_KICKS: [&[(i32, i32)]; 4] = [
+    &[(0, 0), (0, 1)],  // 0 -> 2
+    &[(0, 0), (1, 0)],  // R -> L
+    &[(0, 0), (0, -1)], // 2 -> 0
+    &[(0, 0), (-1, 0)], // L -> R
+];
+
+const IN_PLACE_KICKS: [(i32, i32); 1] = [(0, 0)];
+
+/// The offsets to try, in order, when rotating a piece of the given kind between two rotation
+/// states, either adjacent ones or opposite ones for a half turn. Offsets are in board coordinates
+/// (y grows downwards).
+pub fn kicks(kind: PieceKind, from: RotationState, to: RotationState) -> Vec<(i32, i32)> {
+    use RotationState::*;
+
+    let half_turn = match (from, to) {
+        (Spawn, Reverse) => Some(0),
+        (Right, Left) => Some(1),
+        (Reverse, Spawn) => Some(2),
+        (Left, Right) => Some(3),
+        _ => None,
+    };
+    if let Some(half_turn) = half_turn {
+        let table: &[(i32, i32)] = match kind.definition().kicks {
+            KickTable::InPlace => &IN_PLACE_KICKS,
+            KickTable::Line => LINE_180_KICKS[half_turn],
+            KickTable::Standard => JLSTZ_180_KICKS[half_turn],
+        };
+
+        return table.iter().map(|(x, y)| (*x, -*y)).collect();
+    }
+
+    let transition = match (from, to) {
+        (Spawn, Right) => 0,
+        (Right, Spawn) => 1,
+        (Right, Reverse) => 2,
+        (Reverse, Right) => 3,
+        (Reverse, Left) => 4,
+        (Left, Reverse) => 5,
+        (Left, Spawn) => 6,
+        (Spawn, Left) => 7,
+        _ => panic!("no kick table for {:?} -> {:?}", from, to),
+    };
+
+    let table: &[(i32, i32)] = match kind.definition().kicks {
+        KickTable::InPlace => &IN_PLACE_KICKS,
+        KickTable::Line => &LINE_KICKS[transition],
+        KickTable::Standard => &JLSTZ_KICKS[transition],
+    };
+
+    table.iter().map(|(x, y)| (*x, -*y)).collect()
+}
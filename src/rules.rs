@@ -0,0 +1,102 @@
+use crate::board::{MAX_HIDDEN_ROWS, MAX_PREVIEW_LENGTH};
+use crate::game_mode::GameMode;
+use crate::gravity::{ClearGravity, INSTANT_GRAVITY_LEVEL};
+use serde::{Deserialize, Serialize};
+
+/// Rules holds the settings that change how a game plays. They are chosen when the board is created.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct Rules {
+    pub mode: GameMode,        // the goal of the game and how it ends
+    pub preview_length: usize, // how many upcoming pieces are shown, at most MAX_PREVIEW_LENGTH
+    pub lock_delay: f64,       // ms a piece can rest on the stack before it locks
+    pub lock_reset_limit: u32, // how many moves and rotations can restart the lock delay of a piece
+    pub start_level: u32,      // the level the game starts at, from 1 to INSTANT_GRAVITY_LEVEL
+    pub lines_per_level: u32,  // how many cleared lines take the game to the next level, 0 to stay
+    pub das: f64,              // ms a sideways key has to be held before the piece moves on its own
+    pub arr: f64,              // ms between the moves of a held sideways key, 0 for instant
+    pub soft_drop_factor: f64, // how many times faster pieces fall while soft drop is held
+    pub garbage_messiness: u32, // the chance, out of 100, of the hole moving between garbage rows
+    pub clear_gravity: ClearGravity, // how the cells above cleared rows fall
+    pub line_clear_delay: f64, // ms cleared rows stay on the board before they're removed
+    pub entry_delay: f64,      // ms before the next piece appears, after a lock or line clear
+    pub hidden_rows: i32,      // hidden rows above the board, where pieces spawn, at most MAX_HIDDEN_ROWS
+    pub block_out: bool,       // the game ends when a new piece can't spawn; if not, it moves up
+    pub lock_out: bool,        // the game ends when a piece locks entirely in the hidden rows
+    pub partial_lock_out: bool, // the game ends when a piece locks with any cell in the hidden rows
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            mode: GameMode::Endless,
+            preview_length: 5,
+            lock_delay: 500.0,
+            lock_reset_limit: 15,
+            start_level: 1,
+            lines_per_level: 10,
+            das: 167.0,
+            arr: 33.0,
+            soft_drop_factor: 20.0,
+            garbage_messiness: 0,
+            clear_gravity: ClearGravity::Naive,
+            line_clear_delay: 400.0,
+            entry_delay: 100.0,
+            hidden_rows: 20,
+            block_out: true,
+            lock_out: true,
+            partial_lock_out: false,
+        }
+    }
+}
+
+impl Rules {
+    /// Checks that the settings make sense, saying what's wrong if they don't
+    pub fn validate(&self) -> Result<(), String> {
+        if self.preview_length > MAX_PREVIEW_LENGTH {
+            return Err(format!(
+                "preview_length can be at most {}, not {}",
+                MAX_PREVIEW_LENGTH, self.preview_length
+            ));
+        }
+        if self.lock_delay.is_nan() || self.lock_delay < 0.0 {
+            return Err(format!("lock_delay has to be 0 or more, not {}", self.lock_delay));
+        }
+        for (name, time) in &[
+            ("line_clear_delay", self.line_clear_delay),
+            ("entry_delay", self.entry_delay),
+            ("das", self.das),
+            ("arr", self.arr),
+        ] {
+            if time.is_nan() || *time < 0.0 {
+                return Err(format!("{} has to be 0 or more, not {}", name, time));
+            }
+        }
+        if self.hidden_rows < 0 || self.hidden_rows > MAX_HIDDEN_ROWS {
+            return Err(format!(
+                "hidden_rows has to be between 0 and {}, not {}",
+                MAX_HIDDEN_ROWS, self.hidden_rows
+            ));
+        }
+        if self.start_level == 0 || self.start_level > INSTANT_GRAVITY_LEVEL {
+            return Err(format!(
+                "start_level has to be between 1 and {}, not {}",
+                INSTANT_GRAVITY_LEVEL, self.start_level
+            ));
+        }
+        if self.soft_drop_factor.is_nan() || self.soft_drop_factor < 1.0 {
+            return Err(format!(
+                "soft_drop_factor has to be at least 1, not {}",
+                self.soft_drop_factor
+            ));
+        }
+        if self.garbage_messiness > 100 {
+            return Err(format!(
+                "garbage_messiness is out of 100, not {}",
+                self.garbage_messiness
+            ));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,133 @@
+use serde::Serialize;
+
+/// Points for each piece in a combo after the first one, times the combo count and the level
+pub const COMBO_SCORE: u32 = 50;
+
+/// A triangle piece rotated into a spot it couldn't have been moved into
+#[derive(Serialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TSpin {
+    Mini, // only one of the corners the piece points at is filled
+    Full,
+}
+
+/// What a piece cleared when it locked
+#[derive(Serialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ClearType {
+    Single,
+    Double,
+    Triple,
+    Tetris,
+    TSpinMini, // a T-spin mini that cleared no lines
+    TSpinMiniSingle,
+    TSpinMiniDouble,
+    TSpin, // a T-spin that cleared no lines
+    TSpinSingle,
+    TSpinDouble,
+    TSpinTriple,
+}
+
+impl ClearType {
+    /// Every clear type, in the order they're declared
+    pub const ALL: [ClearType; 11] = [
+        ClearType::Single,
+        ClearType::Double,
+        ClearType::Triple,
+        ClearType::Tetris,
+        ClearType::TSpinMini,
+        ClearType::TSpinMiniSingle,
+        ClearType::TSpinMiniDouble,
+        ClearType::TSpin,
+        ClearType::TSpinSingle,
+        ClearType::TSpinDouble,
+        ClearType::TSpinTriple,
+    ];
+
+    /// The clear type for a number of lines cleared by one piece and the T-spin it was locked
+    /// with, if either happened. A T-spin is scored even when it clears no lines.
+    pub fn new(lines: usize, t_spin: Option<TSpin>) -> Option<Self> {
+        match (t_spin, lines) {
+            (None, 0) => None,
+            (None, 1) => Some(ClearType::Single),
+            (None, 2) => Some(ClearType::Double),
+            (None, 3) => Some(ClearType::Triple),
+            (None, _) => Some(ClearType::Tetris),
+            (Some(TSpin::Mini), 0) => Some(ClearType::TSpinMini),
+            (Some(TSpin::Mini), 1) => Some(ClearType::TSpinMiniSingle),
+            (Some(TSpin::Mini), _) => Some(ClearType::TSpinMiniDouble),
+            (Some(TSpin::Full), 0) => Some(ClearType::TSpin),
+            (Some(TSpin::Full), 1) => Some(ClearType::TSpinSingle),
+            (Some(TSpin::Full), 2) => Some(ClearType::TSpinDouble),
+            (Some(TSpin::Full), _) => Some(ClearType::TSpinTriple),
+        }
+    }
+
+    /// Points for the clear at level 1; the score is multiplied by the level
+    pub fn base_score(self) -> u32 {
+        match self {
+            ClearType::Single => 100,
+            ClearType::Double => 300,
+            ClearType::Triple => 500,
+            ClearType::Tetris => 800,
+            ClearType::TSpinMini => 100,
+            ClearType::TSpinMiniSingle => 200,
+            ClearType::TSpinMiniDouble => 400,
+            ClearType::TSpin => 400,
+            ClearType::TSpinSingle => 800,
+            ClearType::TSpinDouble => 1200,
+            ClearType::TSpinTriple => 1600,
+        }
+    }
+
+    /// Difficult clears done one after another (with no easier clear in between) are worth more.
+    /// T-spins that clear no lines are neither difficult nor easy: they keep the back-to-back going
+    /// without getting its bonus.
+    pub fn is_difficult(self) -> bool {
+        !matches!(
+            self,
+            ClearType::Single | ClearType::Double | ClearType::Triple
+        )
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ClearType::Single => "Single",
+            ClearType::Double => "Double",
+            ClearType::Triple => "Triple",
+            ClearType::Tetris => "Tetris",
+            ClearType::TSpinMini => "T-Spin Mini",
+            ClearType::TSpinMiniSingle => "T-Spin Mini Single",
+            ClearType::TSpinMiniDouble => "T-Spin Mini Double",
+            ClearType::TSpin => "T-Spin",
+            ClearType::TSpinSingle => "T-Spin Single",
+            ClearType::TSpinDouble => "T-Spin Double",
+            ClearType::TSpinTriple => "T-Spin Triple",
+        }
+    }
+}
+
+/// Clear describes the lines cleared by one piece and what they scored
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Clear {
+    pub clear_type: ClearType,
+    pub rows: Vec<i32>, // the cleared rows, top to bottom, as they were before the clear
+    pub back_to_back: bool, // this and the previous clear were both difficult
+    pub combo: u32,       // how many pieces in a row cleared lines before this one
+    pub chain: u32, // 0 for the clear made by the piece, then 1, 2, ... for the cascades it set off
+    pub score: u32,
+}
+
+/// The points for a clear at the given level, including the back-to-back bonus and the combo
+pub fn clear_score(clear_type: ClearType, level: u32, back_to_back: bool, combo: u32) -> u32 {
+    let mut score = clear_type.base_score() * level;
+    if back_to_back {
+        score = score * 3 / 2;
+    }
+
+    score + COMBO_SCORE * combo * level
+}
+
+/// The points for a clear set off by cells cascading into place after an earlier clear; the
+/// further down the chain, the more it's worth
+pub fn chain_score(clear_type: ClearType, level: u32, chain: u32) -> u32 {
+    clear_type.base_score() * level * (chain + 1)
+}
@@ -0,0 +1,1301 @@
+use hello_wasm::board::{Board, Phase, MAX_PREVIEW_LENGTH, MAX_UNDO};
+use hello_wasm::cell::Cell;
+use hello_wasm::game_mode::{GameMode, Outcome};
+use hello_wasm::gravity::ClearGravity;
+use hello_wasm::pieces::{piece_set, Piece, PieceKind};
+use hello_wasm::puzzle;
+use hello_wasm::randomizers::{BagRandomizer, WeightedRandomizer};
+use hello_wasm::rotation::{Kick, RotationState};
+use hello_wasm::rules::Rules;
+use hello_wasm::scoring::ClearType;
+
+const ROWS: i32 = 20;
+const COLS: i32 = 10;
+/// The column pieces spawn at, left of the middle
+const SPAWN_COLUMN: i32 = (COLS - 1) / 2;
+const SEED: u64 = 42;
+
+/// A placed cell for setting up board positions
+const FILLED: Option<Cell> = Some(Cell::new(PieceKind::Square));
+
+/// The default rules without the line clear and entry delays or the hidden rows, so that the next
+/// piece is in play as soon as the last one locks, at the top of the board
+fn plain_rules() -> Rules {
+    Rules {
+        line_clear_delay: 0.0,
+        entry_delay: 0.0,
+        hidden_rows: 0,
+        ..Rules::default()
+    }
+}
+
+fn new_board(seed: u64) -> Board {
+    Board::new(ROWS, COLS, seed, Box::new(BagRandomizer::new(1)), plain_rules())
+}
+
+fn mode_board(mode: GameMode) -> Board {
+    let rules = Rules {
+        mode,
+        ..plain_rules()
+    };
+    Board::new(ROWS, COLS, SEED, Box::new(BagRandomizer::new(1)), rules)
+}
+
+fn preview(preview_length: usize) -> Rules {
+    Rules {
+        preview_length,
+        ..plain_rules()
+    }
+}
+
+/// Fills the given row leaving only the column at `gap` empty
+fn fill_row_except(board: &mut Board, y: i32, gap: i32) {
+    for x in (0..COLS).filter(|x| *x != gap) {
+        board.set_cell(x, y, FILLED);
+    }
+}
+
+fn empty_board(board: &mut Board) {
+    for y in 0..ROWS {
+        for x in 0..COLS {
+            board.set_cell(x, y, None);
+        }
+    }
+}
+
+/// A line piece stood on its end, with its origin at (x, y) and its cells from row y - 1 to y + 2
+fn upright_line(x: i32, y: i32) -> Box<dyn Piece> {
+    let mut piece = PieceKind::Line.create(x, y);
+    piece.rotate_left();
+    piece
+}
+
+/// Empties the board, then clears `lines` lines at the bottom by dropping a line piece into a well
+fn clear_lines(board: &mut Board, lines: i32) {
+    empty_board(board);
+    for y in ROWS - lines..ROWS {
+        fill_row_except(board, y, 0);
+    }
+    board.set_active_piece(upright_line(0, 5));
+    press(board, "Space");
+}
+
+/// Taps a key: presses and releases it
+fn press(board: &mut Board, code: &str) {
+    board.keydown(code, 0.0);
+    board.keyup(code, 0.0);
+    board.process_input();
+}
+
+#[test]
+fn new_board_is_empty() {
+    let board = new_board(SEED);
+
+    assert_eq!(board.cells().len(), ROWS as usize);
+    assert!(board.cells().iter().all(|row| row.len() == COLS as usize));
+    assert!(board.cells().iter().flatten().all(Option::is_none));
+    assert_eq!(board.score, 0);
+    assert!(!board.is_game_over());
+}
+
+#[test]
+fn moves_sideways_until_the_wall() {
+    let mut board = new_board(SEED);
+    board.set_active_piece(PieceKind::Square.create(1, 5));
+
+    press(&mut board, "ArrowLeft");
+    assert_eq!(board.active_piece().get_origin().x, 0);
+
+    press(&mut board, "ArrowLeft");
+    assert_eq!(board.active_piece().get_origin().x, 0);
+}
+
+#[test]
+fn does_not_move_into_placed_cells() {
+    let mut board = new_board(SEED);
+    board.set_active_piece(PieceKind::Square.create(4, 5));
+    board.set_cell(6, 6, FILLED);
+
+    press(&mut board, "ArrowRight");
+    assert_eq!(board.active_piece().get_origin().x, 4);
+}
+
+#[test]
+fn a_held_key_moves_the_piece_after_das_then_every_arr() {
+    let mut board = new_board(SEED);
+    board.set_active_piece(PieceKind::Square.create(4, 5));
+    board.update(1000.0);
+
+    board.keydown("ArrowLeft", 1000.0);
+    board.process_input();
+    assert_eq!(board.active_piece().get_origin().x, 3);
+
+    // the OS repeating the key doesn't move the piece
+    board.keydown("ArrowLeft", 1050.0);
+    board.process_input();
+    board.update(1166.0);
+    assert_eq!(board.active_piece().get_origin().x, 3);
+
+    board.update(1167.0);
+    assert_eq!(board.active_piece().get_origin().x, 2);
+    board.update(1200.0);
+    assert_eq!(board.active_piece().get_origin().x, 1);
+    board.update(1300.0);
+    assert_eq!(board.active_piece().get_origin().x, 0);
+
+    board.keyup("ArrowLeft", 1300.0);
+    board.keydown("ArrowRight", 1300.0);
+    board.keyup("ArrowRight", 1310.0);
+    board.process_input();
+    board.update(1600.0);
+    assert_eq!(board.active_piece().get_origin().x, 1);
+}
+
+#[test]
+fn releasing_the_keys_stops_a_held_key() {
+    let mut board = new_board(SEED);
+    board.set_active_piece(PieceKind::Square.create(4, 5));
+    board.update(1000.0);
+    board.keydown("ArrowLeft", 1000.0);
+    board.keydown("ArrowDown", 1000.0);
+    board.process_input();
+
+    // the page lost focus, so the keyups never come
+    board.release_keys();
+    board.update(1500.0);
+    assert_eq!(board.active_piece().get_origin().x, 3);
+    assert_eq!(board.active_piece().get_origin().y, 5);
+
+    // and the next press isn't mistaken for the OS repeating the key
+    board.keydown("ArrowLeft", 2000.0);
+    board.process_input();
+    assert_eq!(board.active_piece().get_origin().x, 2);
+}
+
+#[test]
+fn an_arr_of_zero_moves_a_held_piece_straight_to_the_wall() {
+    let rules = Rules {
+        arr: 0.0,
+        ..plain_rules()
+    };
+    let mut board = Board::new(ROWS, COLS, SEED, Box::new(BagRandomizer::new(1)), rules);
+    board.set_active_piece(PieceKind::Square.create(4, 5));
+    board.update(1000.0);
+
+    board.keydown("ArrowRight", 1000.0);
+    board.process_input();
+    board.update(1100.0);
+    assert_eq!(board.active_piece().get_origin().x, 5);
+
+    board.update(1167.0);
+    assert_eq!(board.active_piece().get_origin().x, COLS - 2);
+}
+
+#[test]
+fn rotation_kicks_off_the_wall() {
+    let mut board = new_board(SEED);
+    board.set_active_piece(PieceKind::Line.create(1, 5));
+    press(&mut board, "KeyZ");
+    press(&mut board, "ArrowLeft");
+
+    // lying down in place would put the line a column past the wall
+    press(&mut board, "KeyZ");
+
+    let bb = board.active_piece().bounding_box();
+    assert_eq!((bb.x1, bb.x2, bb.y1), (0, 3, 6));
+    assert_eq!(board.rotation_state(), RotationState::Reverse);
+    assert_eq!(board.last_kick(), Some(Kick { test: 2, x: 1, y: 0, half_turn: false }));
+}
+
+#[test]
+fn l_pieces_kick_off_the_wall_with_the_standard_table() {
+    let mut board = new_board(SEED);
+    board.set_active_piece(PieceKind::LLeft.create(1, 5));
+    press(&mut board, "ArrowUp");
+    press(&mut board, "ArrowLeft");
+
+    press(&mut board, "KeyZ");
+
+    let origin: (i32, i32) = board.active_piece().get_origin().into();
+    assert_eq!(origin, (1, 5));
+    assert_eq!(board.rotation_state(), RotationState::Spawn);
+    assert_eq!(board.last_kick(), Some(Kick { test: 1, x: 1, y: 0, half_turn: false }));
+}
+
+#[test]
+fn rotation_kicks_off_the_floor() {
+    let mut board = new_board(SEED);
+    board.set_active_piece(PieceKind::Triangle.create(4, ROWS - 1));
+
+    press(&mut board, "ArrowUp");
+
+    let origin: (i32, i32) = board.active_piece().get_origin().into();
+    assert_eq!(origin, (3, ROWS - 2));
+    assert_eq!(board.rotation_state(), RotationState::Right);
+    assert_eq!(board.last_kick(), Some(Kick { test: 2, x: -1, y: -1, half_turn: false }));
+}
+
+#[test]
+fn a_180_turns_the_piece_in_one_go_with_its_own_kicks() {
+    let mut board = new_board(SEED);
+    for y in 12..ROWS {
+        fill_row_except(&mut board, y, 0);
+        board.set_cell(0, y, FILLED);
+    }
+    // a pocket two rows tall, which a triangle can't stand upright in
+    for y in 17..19 {
+        for x in 3..6 {
+            board.set_cell(x, y, None);
+        }
+    }
+    board.set_active_piece(PieceKind::Triangle.create(4, 18));
+
+    press(&mut board, "ArrowUp");
+    press(&mut board, "KeyZ");
+    assert_eq!(board.rotation_state(), RotationState::Spawn);
+
+    press(&mut board, "KeyA");
+    assert_eq!(board.rotation_state(), RotationState::Reverse);
+    assert_eq!(board.last_kick(), Some(Kick { test: 1, x: 0, y: -1, half_turn: true }));
+    let origin: (i32, i32) = board.active_piece().get_origin().into();
+    assert_eq!(origin, (4, 17));
+}
+
+#[test]
+fn rotation_fails_when_no_kick_fits() {
+    let mut board = new_board(SEED);
+    for y in 0..ROWS {
+        for x in 0..COLS {
+            board.set_cell(x, y, FILLED);
+        }
+    }
+    // a tunnel just long enough for the line to lie in
+    for x in 3..7 {
+        board.set_cell(x, 10, None);
+    }
+    board.set_active_piece(PieceKind::Line.create(4, 10));
+
+    press(&mut board, "ArrowUp");
+
+    assert_eq!(board.rotation_state(), RotationState::Spawn);
+    assert_eq!(board.last_kick(), None);
+    assert_eq!(board.active_piece().bounding_box().x1, 3);
+}
+
+#[test]
+fn placing_a_piece_fills_its_projection() {
+    let mut board = new_board(SEED);
+    board.set_active_piece(PieceKind::Square.create(0, 2));
+
+    press(&mut board, "Space");
+
+    let cells = board.cells();
+    assert!(cells[18][0].is_some() && cells[18][1].is_some());
+    assert!(cells[19][0].is_some() && cells[19][1].is_some());
+}
+
+#[test]
+fn locked_cells_keep_the_kind_and_style_of_their_piece() {
+    let mut board = new_board(SEED);
+    board.set_active_piece(PieceKind::Triangle.create(4, 2));
+
+    press(&mut board, "Space");
+
+    let cell = board.cells()[ROWS as usize - 1][3].unwrap();
+    assert_eq!(cell.kind, Some(PieceKind::Triangle));
+    assert_eq!(cell.style, PieceKind::Triangle.style());
+}
+
+#[test]
+fn gravity_drops_the_piece_one_row() {
+    let mut board = new_board(SEED);
+    board.set_active_piece(PieceKind::Square.create(0, 2));
+
+    board.update(1.0);
+    board.update(1_500.0);
+
+    assert_eq!(board.active_piece().get_origin().y, 3);
+}
+
+#[test]
+fn higher_levels_fall_faster() {
+    let rules = Rules {
+        start_level: 10,
+        ..plain_rules()
+    };
+    let mut board = Board::new(ROWS, COLS, SEED, Box::new(BagRandomizer::new(1)), rules);
+    board.set_active_piece(PieceKind::Square.create(0, 2));
+
+    board.update(1.0);
+    board.update(200.0);
+
+    assert_eq!(board.level(), 10);
+    assert_eq!(board.active_piece().get_origin().y, 5);
+}
+
+#[test]
+fn twenty_g_drops_pieces_straight_to_the_stack() {
+    let rules = Rules {
+        start_level: 20,
+        ..plain_rules()
+    };
+    let mut board = Board::new(ROWS, COLS, SEED, Box::new(BagRandomizer::new(1)), rules);
+    board.set_active_piece(PieceKind::Square.create(0, 2));
+
+    board.update(1.0);
+    board.update(17.0);
+
+    assert_eq!(board.active_piece().get_origin().y, ROWS - 2);
+}
+
+#[test]
+fn level_goes_up_every_ten_lines() {
+    let mut board = new_board(SEED);
+
+    for _ in 0..3 {
+        for y in ROWS - 4..ROWS {
+            fill_row_except(&mut board, y, 0);
+        }
+        board.set_active_piece(upright_line(0, 5));
+        press(&mut board, "Space");
+        board.update(1.0);
+    }
+
+    assert_eq!(board.lines(), 12);
+    assert_eq!(board.level(), 2);
+}
+
+#[test]
+fn completed_rows_are_cleared_and_scored() {
+    let mut board = new_board(SEED);
+    fill_row_except(&mut board, 19, 3);
+    fill_row_except(&mut board, 18, 3);
+    board.set_cell(0, 17, FILLED);
+    board.set_active_piece(upright_line(3, 5));
+
+    press(&mut board, "Space");
+    board.update(1.0);
+
+    assert_eq!(board.score, 300 + 2 * 12);
+    let clears = board.take_clears();
+    assert_eq!(clears.len(), 1);
+    assert_eq!(clears[0].clear_type, ClearType::Double);
+    assert_eq!(clears[0].rows, vec![18, 19]);
+
+    // the two leftover cells of the line piece and the stray cell fell down by two rows
+    let cells = board.cells();
+    assert_eq!(cells[19][0], FILLED);
+    assert_eq!(cells[19][3], Some(Cell::new(PieceKind::Line)));
+    assert_eq!(cells[18][3], Some(Cell::new(PieceKind::Line)));
+    assert_eq!(cells.iter().flatten().filter(|cell| cell.is_some()).count(), 3);
+}
+
+#[test]
+fn cleared_rows_stay_until_the_line_clear_delay_then_the_next_piece_enters() {
+    let mut board = Board::new(ROWS, COLS, SEED, Box::new(BagRandomizer::new(1)), Rules::default());
+    fill_row_except(&mut board, 19, 3);
+    board.set_active_piece(upright_line(3, 5));
+    board.update(1000.0);
+
+    press(&mut board, "Space");
+    assert_eq!(board.phase(), Phase::LineClear(1400.0));
+    assert_eq!(board.last_clear().unwrap().rows, vec![19]);
+    let score = board.score;
+
+    // the cleared row is still there and nothing can be moved or dropped
+    board.update(1399.0);
+    press(&mut board, "Space");
+    assert!(board.cells()[19].iter().all(Option::is_some));
+    assert_eq!(board.score, score);
+
+    board.update(1400.0);
+    assert_eq!(board.phase(), Phase::Entry(1500.0));
+    assert!(board.cells()[19][0].is_none());
+    assert_eq!(board.cells()[19][3], Some(Cell::new(PieceKind::Line)));
+
+    board.update(1500.0);
+    assert_eq!(board.phase(), Phase::Active);
+    assert_eq!(board.active_piece().get_origin().y, -1);
+}
+
+#[test]
+fn pausing_holds_the_delays() {
+    let mut board = Board::new(ROWS, COLS, SEED, Box::new(BagRandomizer::new(1)), Rules::default());
+    board.update(1000.0);
+    press(&mut board, "Space");
+    assert_eq!(board.phase(), Phase::Entry(1100.0));
+
+    // the page stops updating the board while it's paused
+    board.pause();
+    board.resume(5000.0);
+    assert_eq!(board.phase(), Phase::Entry(5100.0));
+
+    board.update(5100.0);
+    assert_eq!(board.phase(), Phase::Active);
+}
+
+#[test]
+fn pausing_holds_the_lock_delay() {
+    let mut board = new_board(SEED);
+    board.set_active_piece(PieceKind::Square.create(0, ROWS - 2));
+    board.update(1.0);
+    board.update(400.0);
+
+    board.pause();
+    board.resume(10_000.0);
+    board.update(10_050.0);
+    assert!(board.cells()[ROWS as usize - 1][0].is_none());
+
+    board.update(10_200.0);
+    assert!(board.cells()[ROWS as usize - 1][0].is_some());
+}
+
+#[test]
+fn game_is_over_when_a_new_piece_cannot_spawn() {
+    let mut board = new_board(SEED);
+    for y in 0..3 {
+        fill_row_except(&mut board, y, 0);
+    }
+    board.set_active_piece(PieceKind::Square.create(8, 10));
+
+    press(&mut board, "Space");
+
+    assert!(board.is_game_over());
+    assert_eq!(board.outcome(), Some(Outcome::ToppedOut));
+}
+
+/// The plain rules with `hidden_rows` rows above the board
+fn hidden_rows(hidden_rows: i32) -> Rules {
+    Rules {
+        hidden_rows,
+        ..plain_rules()
+    }
+}
+
+#[test]
+fn pieces_spawn_in_the_hidden_rows_above_the_board() {
+    let board = Board::new(ROWS, COLS, SEED, Box::new(BagRandomizer::new(1)), hidden_rows(4));
+
+    assert_eq!(board.cells().len(), ROWS as usize);
+    assert_eq!(board.hidden_cells().len(), 4);
+    assert_eq!(board.active_piece().get_origin().y, -1);
+}
+
+#[test]
+fn a_piece_that_locks_in_the_hidden_rows_locks_out() {
+    let mut board = Board::new(ROWS, COLS, SEED, Box::new(BagRandomizer::new(1)), hidden_rows(4));
+    for y in 0..ROWS {
+        fill_row_except(&mut board, y, 9);
+    }
+    board.set_active_piece(PieceKind::Square.create(4, -4));
+
+    press(&mut board, "Space");
+
+    assert!(board.is_game_over());
+    assert_eq!(board.outcome(), Some(Outcome::ToppedOut));
+    assert_eq!(board.hidden_cells()[3][4], Some(Cell::new(PieceKind::Square)));
+}
+
+#[test]
+fn a_piece_that_locks_partly_in_the_hidden_rows_only_tops_out_with_partial_lock_out() {
+    for &partial_lock_out in &[false, true] {
+        let rules = Rules {
+            partial_lock_out,
+            ..hidden_rows(4)
+        };
+        let mut board = Board::new(ROWS, COLS, SEED, Box::new(BagRandomizer::new(1)), rules);
+        for y in 1..ROWS {
+            fill_row_except(&mut board, y, 9);
+        }
+        board.set_active_piece(PieceKind::Square.create(0, -4));
+
+        press(&mut board, "Space");
+
+        assert_eq!(board.is_game_over(), partial_lock_out);
+    }
+}
+
+#[test]
+fn pieces_spawn_inside_the_narrowest_board() {
+    let lines = WeightedRandomizer::new(vec![(PieceKind::Line, 1)]);
+    let mut board = Board::new(ROWS, 4, SEED, Box::new(lines), plain_rules());
+
+    press(&mut board, "Space");
+
+    assert_eq!(board.lines(), 1);
+    assert!(!board.is_game_over());
+}
+
+#[test]
+fn the_first_piece_blocks_out_if_it_cannot_spawn() {
+    let set = r#"[{ "name": "I5", "cells": [[0, 0], [1, 0], [2, 0], [3, 0], [4, 0]], "stroke": "red", "fill": "darkred", "weight": 1 }]"#;
+    let pieces = WeightedRandomizer::new(piece_set::parse(set).unwrap());
+    let board = Board::new(ROWS, 4, SEED, Box::new(pieces), plain_rules());
+
+    assert!(board.is_game_over());
+}
+
+#[test]
+fn without_block_out_a_piece_that_cannot_spawn_moves_up() {
+    for &block_out in &[true, false] {
+        let rules = Rules {
+            block_out,
+            ..hidden_rows(4)
+        };
+        let squares = WeightedRandomizer::new(vec![(PieceKind::Square, 1)]);
+        let mut board = Board::new(ROWS, COLS, SEED, Box::new(squares), rules);
+        board.set_active_piece(PieceKind::Square.create(0, 10));
+        board.set_cell(SPAWN_COLUMN, 0, FILLED);
+
+        press(&mut board, "Space");
+
+        assert_eq!(board.is_game_over(), block_out);
+        if !block_out {
+            assert_eq!(board.active_piece().get_origin().y, -2);
+        }
+    }
+}
+
+/// Plays the same scripted game on a board and returns every piece it dealt
+fn play_scripted_game(board: &mut Board) -> Vec<String> {
+    let script = ["ArrowLeft", "ArrowUp", "ArrowRight", "ArrowRight", "ArrowDown"];
+    let mut dealt = Vec::new();
+
+    for (i, key) in script.iter().cycle().take(60).enumerate() {
+        let time = i as f64 * 50.0;
+        board.keydown(key, time);
+        if i % 3 == 0 {
+            board.keydown("Space", time);
+            board.keyup("Space", time);
+        }
+        if i % 2 == 0 {
+            board.keyup(key, time);
+        }
+        if i % 4 == 0 {
+            board.keyup("ArrowDown", time);
+        }
+        board.process_input();
+        board.update(time + 1.0);
+        dealt.push(board.active_piece().to_string());
+    }
+
+    dealt
+}
+
+#[test]
+fn same_seed_replays_the_same_game() {
+    let mut first = new_board(SEED);
+    let mut second = new_board(SEED);
+
+    assert_eq!(play_scripted_game(&mut first), play_scripted_game(&mut second));
+    assert_eq!(first.cells(), second.cells());
+    assert_eq!(first.score, second.score);
+    assert_eq!(first.is_game_over(), second.is_game_over());
+}
+
+#[test]
+fn different_seeds_deal_different_pieces() {
+    let mut first = new_board(1);
+    let mut second = new_board(2);
+
+    assert_ne!(play_scripted_game(&mut first), play_scripted_game(&mut second));
+}
+
+#[test]
+fn preview_shows_the_pieces_that_are_dealt_next() {
+    let mut board = Board::new(ROWS, COLS, SEED, Box::new(BagRandomizer::new(1)), preview(3));
+    assert_eq!(board.preview().len(), 3);
+
+    for _ in 0..3 {
+        let expected = board.preview()[0];
+        press(&mut board, "Space");
+
+        assert_eq!(
+            board.active_piece().to_string(),
+            expected.create(SPAWN_COLUMN, 1).to_string()
+        );
+    }
+}
+
+#[test]
+fn preview_length_does_not_change_the_pieces_dealt() {
+    let mut without_preview = Board::new(ROWS, COLS, SEED, Box::new(BagRandomizer::new(1)), preview(0));
+    let mut with_preview = Board::new(ROWS, COLS, SEED, Box::new(BagRandomizer::new(1)), preview(6));
+
+    assert_eq!(
+        play_scripted_game(&mut without_preview),
+        play_scripted_game(&mut with_preview)
+    );
+}
+
+#[test]
+fn preview_length_is_capped() {
+    let board = Board::new(ROWS, COLS, SEED, Box::new(BagRandomizer::new(1)), preview(100));
+
+    assert_eq!(board.preview().len(), MAX_PREVIEW_LENGTH);
+}
+
+#[test]
+fn hold_swaps_the_active_piece_once_per_piece() {
+    let mut board = new_board(SEED);
+    let first = board.active_piece().kind();
+    board.set_active_piece(first.create(2, 8));
+
+    press(&mut board, "KeyC");
+    assert_eq!(board.held_piece(), Some(first));
+    assert!(!board.can_hold());
+    let second = board.active_piece().kind();
+
+    // hold is locked until the piece is placed
+    press(&mut board, "KeyC");
+    assert_eq!(board.held_piece(), Some(first));
+    assert_eq!(board.active_piece().kind(), second);
+
+    press(&mut board, "Space");
+    assert!(board.can_hold());
+
+    // swapping brings the held piece back at the spawn position and orientation
+    let third = board.active_piece().kind();
+    press(&mut board, "KeyC");
+    assert_eq!(board.held_piece(), Some(third));
+    assert_eq!(
+        board.active_piece().to_string(),
+        first.create(SPAWN_COLUMN, 1).to_string()
+    );
+}
+
+#[test]
+fn a_resting_piece_locks_after_the_lock_delay() {
+    let mut board = new_board(SEED);
+    board.set_active_piece(PieceKind::Square.create(0, ROWS - 2));
+
+    board.update(1.0);
+    board.update(400.0);
+    assert!(board.cells()[ROWS as usize - 1][0].is_none());
+
+    board.update(600.0);
+    assert!(board.cells()[ROWS as usize - 1][0].is_some());
+}
+
+#[test]
+fn moving_a_resting_piece_restarts_the_lock_delay() {
+    let mut board = new_board(SEED);
+    board.set_active_piece(PieceKind::Square.create(0, ROWS - 2));
+
+    board.update(1.0);
+    board.update(400.0);
+    press(&mut board, "ArrowRight");
+
+    board.update(800.0);
+    assert_eq!(board.active_piece().get_origin().x, 1);
+
+    board.update(1000.0);
+    assert!(board.cells()[ROWS as usize - 1][1].is_some());
+}
+
+#[test]
+fn a_piece_locks_once_it_runs_out_of_lock_resets() {
+    let rules = Rules {
+        lock_reset_limit: 3,
+        ..plain_rules()
+    };
+    let mut board = Board::new(ROWS, COLS, SEED, Box::new(BagRandomizer::new(1)), rules);
+    board.set_active_piece(PieceKind::Square.create(0, ROWS - 2));
+    board.update(1.0);
+
+    for (i, key) in ["ArrowRight", "ArrowLeft", "ArrowRight"].iter().enumerate() {
+        press(&mut board, key);
+        board.update(10.0 * (i + 1) as f64);
+    }
+    assert!(board.cells().iter().flatten().all(Option::is_none));
+
+    press(&mut board, "ArrowLeft");
+    board.update(50.0);
+    assert!(board.cells()[ROWS as usize - 1][0].is_some());
+}
+
+#[test]
+fn hard_drop_scores_two_points_per_square() {
+    let mut board = new_board(SEED);
+    board.set_active_piece(PieceKind::Square.create(0, 2));
+
+    press(&mut board, "Space");
+
+    assert_eq!(board.score, 2 * 16);
+    assert!(board.cells()[ROWS as usize - 1][0].is_some());
+}
+
+#[test]
+fn soft_drop_falls_faster_while_held_and_scores_a_point_per_square() {
+    let mut board = new_board(SEED);
+    board.set_active_piece(PieceKind::Square.create(0, 2));
+    board.update(1.0);
+
+    board.keydown("ArrowDown", 1.0);
+    board.process_input();
+    board.update(201.0);
+    assert_eq!(board.active_piece().get_origin().y, 5);
+    assert_eq!(board.score, 3);
+
+    board.keyup("ArrowDown", 201.0);
+    board.process_input();
+    board.update(400.0);
+    assert_eq!(board.active_piece().get_origin().y, 5);
+    assert_eq!(board.score, 3);
+}
+
+#[test]
+fn clears_score_by_type_and_level() {
+    let rules = Rules {
+        start_level: 3,
+        ..plain_rules()
+    };
+    let mut board = Board::new(ROWS, COLS, SEED, Box::new(BagRandomizer::new(1)), rules);
+
+    clear_lines(&mut board, 4);
+
+    let clear = board.last_clear().unwrap();
+    assert_eq!(clear.clear_type, ClearType::Tetris);
+    assert_eq!(clear.score, 800 * 3);
+    assert_eq!(board.score, 800 * 3 + 2 * 12);
+}
+
+#[test]
+fn consecutive_tetrises_are_back_to_back() {
+    let mut board = new_board(SEED);
+
+    clear_lines(&mut board, 4);
+    assert!(!board.last_clear().unwrap().back_to_back);
+
+    clear_lines(&mut board, 4);
+    let clear = board.last_clear().unwrap();
+    assert!(clear.back_to_back);
+    assert_eq!(clear.combo, 1);
+    assert_eq!(clear.score, 1200 + 50);
+}
+
+#[test]
+fn an_easier_clear_breaks_back_to_back() {
+    let mut board = new_board(SEED);
+
+    clear_lines(&mut board, 4);
+    clear_lines(&mut board, 1);
+    clear_lines(&mut board, 4);
+
+    assert!(!board.last_clear().unwrap().back_to_back);
+}
+
+#[test]
+fn a_piece_that_clears_nothing_ends_the_combo() {
+    let mut board = new_board(SEED);
+
+    clear_lines(&mut board, 1);
+    clear_lines(&mut board, 2);
+    assert_eq!(board.last_clear().unwrap().combo, 1);
+    assert_eq!(board.last_clear().unwrap().score, 300 + 50);
+
+    empty_board(&mut board);
+    board.set_active_piece(PieceKind::Square.create(4, 2));
+    press(&mut board, "Space");
+
+    clear_lines(&mut board, 1);
+    assert_eq!(board.last_clear().unwrap().combo, 0);
+}
+
+#[test]
+fn cascade_gravity_drops_clusters_into_holes_and_chains_clears() {
+    let rules = Rules {
+        clear_gravity: ClearGravity::Cascade,
+        ..plain_rules()
+    };
+    let mut board = Board::new(ROWS, COLS, SEED, Box::new(BagRandomizer::new(1)), rules);
+    fill_row_except(&mut board, 19, 0);
+    for x in (1..COLS).filter(|x| *x != 5) {
+        board.set_cell(x, 18, FILLED);
+    }
+    // a lone cell over the hole in row 18, which naive gravity would leave hanging
+    board.set_cell(5, 15, FILLED);
+    board.set_active_piece(upright_line(0, 5));
+
+    press(&mut board, "Space");
+
+    // clearing row 19 lets row 18 and the lone cell fall into it, which clears it again
+    let clears = board.take_clears();
+    assert_eq!(clears.len(), 2);
+    assert_eq!((clears[0].clear_type, clears[0].chain), (ClearType::Single, 0));
+    assert_eq!((clears[1].clear_type, clears[1].chain), (ClearType::Single, 1));
+    assert_eq!(clears[1].rows, vec![19]);
+    assert_eq!(clears[1].score, 100 * 2);
+    assert_eq!(board.score, 100 + 100 * 2 + 2 * 12);
+    assert_eq!(board.lines(), 2);
+
+    // the rest of the line piece fell to the floor
+    let cells = board.cells();
+    assert_eq!(cells[19][0], Some(Cell::new(PieceKind::Line)));
+    assert_eq!(cells[18][0], Some(Cell::new(PieceKind::Line)));
+    assert_eq!(cells.iter().flatten().filter(|cell| cell.is_some()).count(), 2);
+}
+
+/// Digs a slot for a triangle pointing down at (4, 18), with an overhang over its left side when
+/// `overhang` is set
+fn t_slot(board: &mut Board, overhang: bool) {
+    empty_board(board);
+    fill_row_except(board, ROWS - 1, 4);
+    fill_row_except(board, ROWS - 2, 4);
+    board.set_cell(3, ROWS - 2, None);
+    board.set_cell(5, ROWS - 2, None);
+    board.set_cell(3, ROWS - 3, if overhang { FILLED } else { None });
+}
+
+#[test]
+fn rotating_a_triangle_into_a_slot_is_a_t_spin() {
+    let mut board = new_board(SEED);
+    t_slot(&mut board, true);
+    board.set_active_piece(PieceKind::Triangle.create(4, 18));
+
+    press(&mut board, "ArrowUp");
+    press(&mut board, "ArrowUp");
+    press(&mut board, "Space");
+
+    let clear = board.last_clear().unwrap();
+    assert_eq!(clear.clear_type, ClearType::TSpinDouble);
+    assert_eq!(clear.rows, vec![18, 19]);
+    assert_eq!(board.score, 1200);
+}
+
+#[test]
+fn a_t_spin_with_one_front_corner_filled_is_a_mini() {
+    let mut board = new_board(SEED);
+    t_slot(&mut board, true);
+    board.set_active_piece(PieceKind::Triangle.create(4, 18));
+
+    // pointing up, with only the left of the two corners above it filled
+    press(&mut board, "ArrowUp");
+    press(&mut board, "KeyZ");
+    press(&mut board, "Space");
+
+    let clear = board.last_clear().unwrap();
+    assert_eq!(clear.clear_type, ClearType::TSpinMiniSingle);
+    assert_eq!(clear.score, 200);
+}
+
+#[test]
+fn the_fifth_test_of_a_180_does_not_make_a_t_spin_full() {
+    let mut board = new_board(SEED);
+    board.set_cell(5, 12, FILLED);
+    board.set_cell(5, 14, FILLED);
+    board.set_cell(3, 14, FILLED);
+    board.set_active_piece(PieceKind::Triangle.create(4, 15));
+    press(&mut board, "ArrowUp");
+    board.set_cell(3, 15, FILLED);
+
+    // pointing right, the first four tests are blocked and the fifth lifts the triangle two rows
+    // into a spot with three corners filled, but only one of the two it points at
+    press(&mut board, "KeyA");
+    assert_eq!(
+        board.last_kick(),
+        Some(Kick { test: 4, x: 0, y: -2, half_turn: true })
+    );
+    press(&mut board, "Space");
+
+    assert_eq!(board.last_clear().unwrap().clear_type, ClearType::TSpinMini);
+}
+
+#[test]
+fn a_triangle_dropped_into_a_slot_is_not_a_t_spin() {
+    let mut board = new_board(SEED);
+    t_slot(&mut board, false);
+    let mut piece = PieceKind::Triangle.create(4, 5);
+    piece.rotate_right();
+    piece.rotate_right();
+    board.set_active_piece(piece);
+
+    press(&mut board, "Space");
+
+    let clear = board.last_clear().unwrap();
+    assert_eq!(clear.clear_type, ClearType::Double);
+    assert_eq!(clear.score, 300);
+}
+
+#[test]
+fn an_eraser_clears_the_cells_it_lands_on() {
+    let mut board = new_board(SEED);
+    fill_row_except(&mut board, ROWS - 1, 9);
+    fill_row_except(&mut board, ROWS - 2, 9);
+    board.set_active_piece(PieceKind::Eraser.create(0, 2));
+
+    press(&mut board, "Space");
+
+    // it sinks into the stack by one row and empties everything it covers
+    let cells = board.cells();
+    assert!(cells[17][0].is_none() && cells[17][1].is_none());
+    assert!(cells[18][0].is_none() && cells[18][1].is_none());
+    assert!(cells[19][0].is_some() && cells[19][1].is_some());
+    assert!(cells[18][2].is_some());
+    assert_eq!(board.score, 2 * 15);
+}
+
+#[test]
+fn an_eraser_moves_through_placed_cells() {
+    let mut board = new_board(SEED);
+    for y in 10..ROWS {
+        board.set_cell(2, y, FILLED);
+    }
+    board.set_active_piece(PieceKind::Eraser.create(0, 10));
+
+    press(&mut board, "ArrowRight");
+    assert_eq!(board.active_piece().get_origin().x, 1);
+
+    // once it overlaps the stack it has landed, and it locks where it is
+    board.update(1.0);
+    board.update(600.0);
+    let cells = board.cells();
+    assert!(cells[10][2].is_none() && cells[11][2].is_none());
+    assert!(cells[12][2].is_some());
+}
+
+#[test]
+fn garbage_pushes_the_stack_and_the_active_piece_up() {
+    let mut board = new_board(SEED);
+    board.set_cell(0, ROWS - 1, FILLED);
+    board.set_active_piece(PieceKind::Square.create(4, 10));
+
+    board.add_garbage(&[3, 5]).unwrap();
+
+    let cells = board.cells();
+    assert_eq!(cells[19][5], None);
+    assert_eq!(cells[18][3], None);
+    assert_eq!(cells[19][0], Some(Cell::garbage()));
+    assert_eq!(cells.iter().flatten().filter(|cell| cell.is_some()).count(), 19);
+    assert_eq!(cells[17][0], FILLED);
+    assert_eq!(board.active_piece().get_origin().y, 8);
+    assert!(!board.is_game_over());
+}
+
+#[test]
+fn garbage_that_pushes_the_stack_out_of_the_board_ends_the_game() {
+    let mut board = new_board(SEED);
+    board.set_cell(0, 0, FILLED);
+
+    board.add_garbage(&[4]).unwrap();
+
+    assert!(board.is_game_over());
+}
+
+#[test]
+fn garbage_with_a_hole_outside_the_board_is_rejected() {
+    let mut board = new_board(SEED);
+
+    assert!(board.add_garbage(&[3, COLS]).is_err());
+    assert!(board.add_garbage(&[-1]).is_err());
+    assert!(board.cells().iter().flatten().all(Option::is_none));
+}
+
+/// The pieces dealt by dropping `pieces` of them in Zen, with no preview, adding a row of random
+/// garbage after each one when `garbage` is set
+fn pieces_dealt_with_garbage(pieces: usize, garbage: bool) -> Vec<PieceKind> {
+    let rules = Rules {
+        mode: GameMode::Zen,
+        ..preview(0)
+    };
+    let mut board = Board::new(ROWS, COLS, SEED, Box::new(BagRandomizer::new(1)), rules);
+
+    let mut dealt = Vec::new();
+    for _ in 0..pieces {
+        dealt.push(board.active_piece().kind());
+        press(&mut board, "Space");
+        if garbage {
+            board.add_random_garbage(1);
+        }
+    }
+
+    dealt
+}
+
+#[test]
+fn garbage_does_not_change_the_pieces_that_are_dealt() {
+    assert_eq!(
+        pieces_dealt_with_garbage(30, true),
+        pieces_dealt_with_garbage(30, false)
+    );
+}
+
+#[test]
+fn garbage_messiness_moves_the_hole() {
+    let clean = Rules {
+        garbage_messiness: 0,
+        ..plain_rules()
+    };
+    let mut board = Board::new(ROWS, COLS, SEED, Box::new(BagRandomizer::new(1)), clean);
+    board.add_random_garbage(8);
+    let holes = garbage_holes(&board);
+    assert_eq!(holes.len(), 8);
+    assert!(holes.iter().all(|hole| *hole == holes[0]));
+
+    let messy = Rules {
+        garbage_messiness: 100,
+        ..plain_rules()
+    };
+    let mut board = Board::new(ROWS, COLS, SEED, Box::new(BagRandomizer::new(1)), messy);
+    board.add_random_garbage(8);
+    let holes = garbage_holes(&board);
+    assert!(holes.windows(2).all(|pair| pair[0] != pair[1]));
+}
+
+/// The hole column of each garbage row, top to bottom
+fn garbage_holes(board: &Board) -> Vec<usize> {
+    board
+        .cells()
+        .iter()
+        .filter(|row| row.contains(&Some(Cell::garbage())))
+        .map(|row| row.iter().position(Option::is_none).unwrap())
+        .collect()
+}
+
+#[test]
+fn sprint_is_complete_after_forty_lines() {
+    let mut board = mode_board(GameMode::Sprint);
+
+    for _ in 0..9 {
+        clear_lines(&mut board, 4);
+    }
+    assert!(!board.is_game_over());
+
+    clear_lines(&mut board, 4);
+    assert!(board.is_game_over());
+    assert_eq!(board.outcome(), Some(Outcome::Completed));
+}
+
+#[test]
+fn marathon_is_complete_at_level_fifteen() {
+    let rules = Rules {
+        mode: GameMode::Marathon,
+        start_level: 14,
+        ..plain_rules()
+    };
+    let mut board = Board::new(ROWS, COLS, SEED, Box::new(BagRandomizer::new(1)), rules);
+
+    clear_lines(&mut board, 4);
+    clear_lines(&mut board, 4);
+    assert!(!board.is_game_over());
+
+    clear_lines(&mut board, 2);
+    assert_eq!(board.level(), 15);
+    assert_eq!(board.outcome(), Some(Outcome::Completed));
+}
+
+#[test]
+fn ultra_ends_after_two_minutes_of_play() {
+    let mut board = mode_board(GameMode::Ultra);
+    board.update(1.0);
+    board.update(60_001.0);
+
+    // time spent paused doesn't count
+    board.pause();
+    board.resume(200_000.0);
+    board.update(259_990.0);
+    assert!(!board.is_game_over());
+
+    board.update(260_010.0);
+    assert!(board.is_game_over());
+    assert_eq!(board.outcome(), Some(Outcome::Completed));
+}
+
+#[test]
+fn zen_empties_the_board_instead_of_topping_out() {
+    let mut board = mode_board(GameMode::Zen);
+    for y in 0..3 {
+        fill_row_except(&mut board, y, 0);
+    }
+    board.set_active_piece(PieceKind::Square.create(8, 10));
+
+    press(&mut board, "Space");
+
+    assert!(!board.is_game_over());
+    assert!(board.cells().iter().flatten().all(Option::is_none));
+}
+
+/// Two puzzles on a board with a well in the right column: a Tetris with a line piece, then two
+/// lines with a square and a line piece
+const PUZZLES: &str = "
+    name: Tetris
+    goal: Tetris
+    pieces: Line
+    #########.
+    #########.
+    #########.
+    #########.
+
+    name: Double
+    goal: 2 lines
+    pieces: Square Line
+    ########..
+    ########..";
+
+#[test]
+fn a_solved_puzzle_moves_on_to_the_next_one() {
+    let mut board = new_board(SEED);
+    board.load_puzzles(puzzle::parse(PUZZLES).unwrap()).unwrap();
+
+    assert_eq!(board.rules().mode, GameMode::Puzzle);
+    assert_eq!(board.active_piece().kind(), PieceKind::Line);
+    assert!(board.preview().is_empty());
+    assert_eq!(board.cells()[ROWS as usize - 4][0], Some(Cell::garbage()));
+
+    press(&mut board, "ArrowUp");
+    for _ in 0..4 {
+        press(&mut board, "ArrowRight");
+    }
+    press(&mut board, "Space");
+
+    let run = board.puzzles().unwrap();
+    assert_eq!(run.solved, vec![true]);
+    assert_eq!(run.puzzle().name, "Double");
+    assert_eq!(board.active_piece().kind(), PieceKind::Square);
+    assert_eq!(board.preview(), vec![PieceKind::Line]);
+    assert_eq!(board.cells()[ROWS as usize - 2][0], Some(Cell::garbage()));
+    assert!(board.cells()[ROWS as usize - 4][0].is_none());
+}
+
+#[test]
+fn a_puzzle_fails_when_it_runs_out_of_pieces_and_the_last_one_ends_the_game() {
+    let mut board = new_board(SEED);
+    board.load_puzzles(puzzle::parse(PUZZLES).unwrap()).unwrap();
+
+    // the line piece is dropped in the middle, which clears nothing
+    press(&mut board, "Space");
+    assert_eq!(board.puzzles().unwrap().solved, vec![false]);
+    assert!(!board.is_game_over());
+
+    for _ in 0..4 {
+        press(&mut board, "ArrowRight");
+    }
+    press(&mut board, "Space");
+    press(&mut board, "Space");
+
+    assert_eq!(board.puzzles().unwrap().solved, vec![false, true]);
+    assert!(board.is_game_over());
+    assert_eq!(board.outcome(), Some(Outcome::Completed));
+}
+
+#[test]
+fn a_puzzle_that_blocks_out_fails_straight_away() {
+    // the first puzzle has a cell where its line piece spawns
+    let blocked = format!(
+        "name: Blocked\ngoal: 1 line\npieces: Line\n.....#....\n{}",
+        "..........\n".repeat(18)
+    );
+    let source = format!("{}\n\n{}", blocked, PUZZLES);
+    let mut board = new_board(SEED);
+    board.load_puzzles(puzzle::parse(&source).unwrap()).unwrap();
+
+    let run = board.puzzles().unwrap();
+    assert_eq!(run.solved, vec![false]);
+    assert_eq!(run.puzzle().name, "Tetris");
+    assert_eq!(board.active_piece().kind(), PieceKind::Line);
+    assert!(board.can_hold());
+    assert_eq!(board.cells()[ROWS as usize - 4][0], Some(Cell::garbage()));
+    assert!(board.cells()[1][5].is_none());
+    assert!(!board.is_game_over());
+}
+
+#[test]
+fn a_held_piece_is_dealt_once_a_puzzle_has_no_others_left() {
+    let mut board = new_board(SEED);
+    board.load_puzzles(puzzle::parse(PUZZLES).unwrap()).unwrap();
+    press(&mut board, "Space");
+
+    // the square is held and the line dealt instead, then the square comes back after it
+    press(&mut board, "KeyC");
+    assert_eq!(board.active_piece().kind(), PieceKind::Line);
+    press(&mut board, "Space");
+
+    assert_eq!(board.active_piece().kind(), PieceKind::Square);
+    assert_eq!(board.held_piece(), None);
+    assert_eq!(board.puzzles().unwrap().solved, vec![false]);
+}
+
+/// What can be seen of a board: its cells, score, active piece and the pieces after it
+fn position(board: &Board) -> (Vec<Vec<Option<Cell>>>, u32, PieceKind, Vec<PieceKind>) {
+    (
+        board.cells().to_vec(),
+        board.score,
+        board.active_piece().kind(),
+        board.preview(),
+    )
+}
+
+#[test]
+fn undo_takes_back_placements_and_the_same_pieces_are_dealt_again() {
+    let mut board = mode_board(GameMode::Zen);
+    let start = position(&board);
+    press(&mut board, "Space");
+    let after_one = position(&board);
+    for _ in 0..9 {
+        press(&mut board, "ArrowLeft");
+        press(&mut board, "Space");
+    }
+    let after_ten = position(&board);
+
+    assert_eq!(board.undo(9), 9);
+    assert_eq!(position(&board), after_one);
+
+    press(&mut board, "Backspace");
+    assert_eq!(position(&board), start);
+    assert_eq!(board.active_piece().get_origin().y, 1);
+    assert_eq!(board.undo(1), 0);
+
+    press(&mut board, "Space");
+    for _ in 0..9 {
+        press(&mut board, "ArrowLeft");
+        press(&mut board, "Space");
+    }
+    assert_eq!(position(&board), after_ten);
+}
+
+#[test]
+fn undo_is_only_allowed_in_practice_modes() {
+    let mut board = mode_board(GameMode::Sprint);
+    press(&mut board, "Space");
+    let placed = position(&board);
+
+    assert_eq!(board.undo(1), 0);
+    press(&mut board, "Backspace");
+    assert_eq!(position(&board), placed);
+}
+
+#[test]
+fn undo_goes_back_at_most_max_undo_placements() {
+    let mut board = mode_board(GameMode::Zen);
+    for _ in 0..MAX_UNDO + 10 {
+        press(&mut board, "Space");
+    }
+
+    assert_eq!(board.undo(MAX_UNDO + 10), MAX_UNDO);
+}
+
+#[test]
+fn a_custom_piece_set_replaces_the_pieces_that_are_dealt() {
+    let mut board = new_board(SEED);
+    let set = r#"[{ "name": "I3", "cells": [[-1, 0], [0, 0], [1, 0]], "stroke": "red", "fill": "darkred", "weight": 1 }]"#;
+    let pieces = piece_set::parse(set).unwrap();
+    let tromino = pieces[0].0;
+
+    board.set_randomizer(Box::new(WeightedRandomizer::new(pieces)));
+    assert!(board.preview().iter().all(|kind| *kind == tromino));
+
+    press(&mut board, "Space");
+    assert_eq!(board.active_piece().kind(), tromino);
+
+    press(&mut board, "Space");
+    let placed: Vec<Cell> = board
+        .cells()
+        .iter()
+        .flatten()
+        .flatten()
+        .filter(|cell| cell.kind == Some(tromino))
+        .copied()
+        .collect();
+    assert_eq!(placed.len(), 3);
+    assert_eq!(placed[0].style.fill, "darkred");
+}
@@ -0,0 +1,59 @@
+use hello_wasm::config::Config;
+use hello_wasm::game_mode::GameMode;
+
+#[test]
+fn an_empty_config_uses_the_defaults() {
+    let config = Config::from_json("{}").unwrap();
+
+    assert_eq!((config.rows, config.cols), (20, 10));
+    assert_eq!(config.cell_size, 30);
+    assert_eq!(config.canvas_id, "canvas");
+    assert_eq!(config.rules.mode, GameMode::Endless);
+}
+
+#[test]
+fn rules_can_be_set_one_at_a_time() {
+    let config =
+        Config::from_json(r#"{ "cols": 12, "rules": { "mode": "sprint", "preview_length": 3 } }"#)
+            .unwrap();
+
+    assert_eq!((config.rows, config.cols), (20, 12));
+    assert_eq!(config.rules.mode, GameMode::Sprint);
+    assert_eq!(config.rules.preview_length, 3);
+    assert_eq!(config.rules.lock_delay, 500.0);
+}
+
+#[test]
+fn invalid_configs_are_rejected() {
+    for json in &[
+        r#"{ "rows": 2 }"#,
+        r#"{ "cols": 1000 }"#,
+        r#"{ "cell_size": 0 }"#,
+        r#"{ "canvas_id": "" }"#,
+        r#"{ "colums": 12 }"#,
+        r#"{ "rules": { "mode": "tetris" } }"#,
+        r#"{ "rules": { "preview_length": 50 } }"#,
+        r#"{ "rules": { "start_level": 0 } }"#,
+        r#"{ "rules": { "start_level": 4000000000 } }"#,
+        r#"{ "rules": { "garbage_messiness": 101 } }"#,
+        r#"{ "rows": "twenty" }"#,
+        r#"{ "peek_rows": 3, "rules": { "hidden_rows": 2 } }"#,
+        r#"{ "rules": { "hidden_rows": -1 } }"#,
+    ] {
+        assert!(Config::from_json(json).is_err(), "{}", json);
+    }
+}
+
+#[test]
+fn auto_fit_uses_the_biggest_cells_that_fit() {
+    let mut config = Config::from_json(r#"{ "auto_fit": true }"#).unwrap();
+    // the board is 20 rows tall with 2 hidden rows peeked at above it, so the height is what
+    // limits the size on a wide canvas
+    assert_eq!(config.pixels_per_cell(4000.0, 880.0), Ok(40));
+
+    config.auto_fit = false;
+    assert_eq!(config.pixels_per_cell(4000.0, 880.0), Ok(30));
+
+    config.auto_fit = true;
+    assert!(config.pixels_per_cell(10.0, 10.0).is_err());
+}
@@ -0,0 +1,126 @@
+use hello_wasm::pieces::{piece_set, Piece, PieceKind};
+
+const KINDS: [PieceKind; 8] = [
+    PieceKind::Line,
+    PieceKind::Square,
+    PieceKind::LLeft,
+    PieceKind::LRight,
+    PieceKind::ZLeft,
+    PieceKind::ZRight,
+    PieceKind::Triangle,
+    PieceKind::Eraser,
+];
+
+/// The cells of a piece created at (5, 5), relative to where it was created
+fn offsets(piece: &dyn Piece) -> Vec<(i32, i32)> {
+    piece
+        .mask()
+        .iter()
+        .map(|cell| (cell.x - 5, cell.y - 5))
+        .collect()
+}
+
+#[test]
+fn every_piece_has_four_cells_in_every_orientation() {
+    for kind in KINDS.iter() {
+        for orientation in kind.definition().orientations {
+            assert_eq!(orientation.len(), 4, "{:?}", kind);
+        }
+    }
+}
+
+#[test]
+fn rotating_a_piece_goes_through_its_orientations_and_back() {
+    for kind in KINDS.iter() {
+        let spawned = offsets(kind.create(5, 5).as_ref());
+
+        let mut piece = kind.create(5, 5);
+        for _ in kind.definition().orientations {
+            piece.rotate_right();
+        }
+        assert_eq!(offsets(piece.as_ref()), spawned, "{:?}", kind);
+
+        piece.rotate_right();
+        piece.rotate_left();
+        assert_eq!(offsets(piece.as_ref()), spawned, "{:?}", kind);
+    }
+}
+
+#[test]
+fn a_180_is_the_orientation_two_quarter_turns_away() {
+    for kind in KINDS.iter() {
+        let mut turned_twice = kind.create(5, 5);
+        turned_twice.rotate_right();
+        turned_twice.rotate_right();
+
+        let mut half_turned = kind.create(5, 5);
+        half_turned.rotate_180();
+        assert_eq!(offsets(half_turned.as_ref()), offsets(turned_twice.as_ref()), "{:?}", kind);
+    }
+}
+
+#[test]
+fn the_bounding_box_fits_the_cells() {
+    let mut piece = PieceKind::Triangle.create(5, 5);
+    piece.rotate_right();
+
+    let bb = piece.bounding_box();
+    assert_eq!((bb.x1, bb.y1, bb.x2, bb.y2), (5, 4, 6, 6));
+}
+
+const TROMINOES: &str = r#"[
+    { "name": "I3", "cells": [[-1, 0], [0, 0], [1, 0]], "stroke": "red", "fill": "darkred", "weight": 1 },
+    { "name": "L3", "cells": [[0, 0], [1, 0], [0, 1]], "stroke": "cyan", "fill": "teal", "weight": 2 }
+]"#;
+
+#[test]
+fn a_piece_set_is_read_from_json() {
+    let pieces = piece_set::parse(TROMINOES).unwrap();
+
+    assert_eq!(pieces.len(), 2);
+    let (line, weight) = pieces[0];
+    assert_eq!(line.definition().name, "I3");
+    assert_eq!(line.style().fill, "darkred");
+    assert_eq!(weight, 1);
+    assert_eq!(line.definition().orientations.len(), 2);
+    assert_eq!(pieces[1].0.definition().orientations.len(), 4);
+
+    let mut piece = line.create(5, 5);
+    piece.rotate_right();
+    assert_eq!(offsets(piece.as_ref()), vec![(0, -1), (0, 0), (0, 1)]);
+}
+
+#[test]
+fn invalid_piece_sets_are_rejected_with_a_reason() {
+    let piece = |cells: &str| {
+        format!(
+            r#"[{{ "name": "P", "cells": {}, "stroke": "red", "fill": "red", "weight": 1 }}]"#,
+            cells
+        )
+    };
+    let error = |json: &str| piece_set::parse(json).unwrap_err();
+
+    assert!(error("[]").contains("at least one piece"));
+    assert!(error("{").contains("invalid piece set"));
+    assert!(error(&piece("[]")).contains("no cells"));
+    assert!(error(&piece("[[0, 0], [0, 0]]")).contains("more than once"));
+    assert!(error(&piece("[[0, 0], [2, 0]]")).contains("aren't connected"));
+    assert!(
+        error(&piece("[[0, 0], [0, 1], [0, 2], [0, 3], [0, 4], [0, 5]]")).contains("wide or tall")
+    );
+    assert!(error(&piece("[[0, -2], [0, -1]]")).contains("can't spawn"));
+    assert!(error(&piece("[[2147483647, 0], [-2147483648, 0]]")).contains("cells from the one"));
+    assert!(error(&TROMINOES.replace("I3", "L3")).contains("more than one piece named"));
+    assert!(error(
+        &TROMINOES
+            .replace("\"weight\": 1", "\"weight\": 0")
+            .replace("\"weight\": 2", "\"weight\": 0")
+    )
+    .contains("weight above 0"));
+    assert!(error(
+        &TROMINOES
+            .replace("\"weight\": 2", "\"weight\": 2147483648")
+            .replace("\"weight\": 1", "\"weight\": 2147483648")
+    )
+    .contains("add up to more than"));
+}
@@ -0,0 +1,85 @@
+use hello_wasm::pieces::PieceKind;
+use hello_wasm::puzzle::{self, Goal};
+use hello_wasm::scoring::ClearType;
+
+#[test]
+fn puzzles_are_read_from_text() {
+    let puzzles = puzzle::parse(
+        "name: Well
+         goal: 4 lines
+         pieces: Line Square
+         #########.
+         #########.
+
+         name: Empty
+         goal: perfect clear
+         pieces: Square",
+    )
+    .unwrap();
+
+    assert_eq!(puzzles.len(), 2);
+    assert_eq!(puzzles[0].name, "Well");
+    assert_eq!(puzzles[0].goal, Goal::Lines(4));
+    assert_eq!(puzzles[0].pieces, vec![PieceKind::Line, PieceKind::Square]);
+    assert_eq!(puzzles[0].board.len(), 2);
+    assert_eq!(puzzles[0].width(), 10);
+    assert!(!puzzles[0].board[0][9]);
+    assert_eq!(puzzles[1].goal, Goal::PerfectClear);
+    assert!(puzzles[1].board.is_empty());
+}
+
+#[test]
+fn puzzles_are_read_from_json() {
+    let puzzles = puzzle::parse(
+        r#"[{ "name": "Slot", "goal": "t-spin double", "pieces": ["Triangle"], "board": [".#..", "..##"] }]"#,
+    )
+    .unwrap();
+
+    assert_eq!(puzzles[0].goal, Goal::Clear(ClearType::TSpinDouble));
+    assert_eq!(
+        puzzles[0].board,
+        vec![
+            vec![false, true, false, false],
+            vec![false, false, true, true]
+        ]
+    );
+}
+
+#[test]
+fn goals_are_written_the_way_they_are_read() {
+    for goal in &[
+        Goal::Lines(1),
+        Goal::Lines(3),
+        Goal::PerfectClear,
+        Goal::Clear(ClearType::TSpinMiniSingle),
+    ] {
+        assert_eq!(Goal::parse(&goal.to_string()), Ok(*goal));
+    }
+}
+
+#[test]
+fn invalid_puzzles_are_rejected() {
+    for source in &[
+        "",
+        "[]",
+        "name: a\ngoal: 0 lines\npieces: Line",
+        "name: a\ngoal: win\npieces: Line",
+        "name: a\ngoal: 1 line\npieces: Pentomino",
+        "name: a\ngoal: 1 line\npieces:",
+        "name: a\npieces: Line",
+        "name: a\ngoal: 1 line\npieces: Line\n##x.",
+        "name: a\ngoal: 1 line\npieces: Line\n####",
+        "name: a\ngoal: 1 line\npieces: Line\n#..\n#.",
+        "name: a\ncolor: red\ngoal: 1 line\npieces: Line",
+        r#"[{ "name": "a", "goal": "1 line", "pieces": ["Line"], "board": [], "level": 2 }]"#,
+    ] {
+        assert!(puzzle::parse(source).is_err(), "{:?}", source);
+    }
+}
+
+#[test]
+fn the_puzzles_that_come_with_the_game_are_valid() {
+    let puzzles = puzzle::parse(include_str!("../puzzles.txt")).unwrap();
+
+    assert!(puzzles.iter().all(|puzzle| puzzle.width() == 10));
+}
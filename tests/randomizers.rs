@@ -0,0 +1,114 @@
+use hello_wasm::pieces::PieceKind;
+use hello_wasm::randomizers::{
+    from_name, BagRandomizer, EraserRandomizer, HistoryRandomizer, Randomizer, UniformRandomizer,
+    WeightedRandomizer,
+};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::collections::HashMap;
+
+fn deal(randomizer: &mut dyn Randomizer, count: usize) -> Vec<PieceKind> {
+    let mut rng = StdRng::seed_from_u64(7);
+    (0..count).map(|_| randomizer.next(&mut rng)).collect()
+}
+
+fn count(pieces: &[PieceKind]) -> HashMap<PieceKind, usize> {
+    let mut counts = HashMap::new();
+    for piece in pieces {
+        *counts.entry(*piece).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// The longest run of pieces dealt without `kind` among them
+fn longest_drought(pieces: &[PieceKind], kind: PieceKind) -> usize {
+    pieces
+        .split(|piece| *piece == kind)
+        .map(|run| run.len())
+        .max()
+        .unwrap_or(0)
+}
+
+#[test]
+fn seven_bag_deals_every_piece_once_per_bag() {
+    let pieces = deal(&mut BagRandomizer::new(1), 7 * 20);
+
+    for bag in pieces.chunks(7) {
+        let counts = count(bag);
+        assert!(PieceKind::ALL.iter().all(|kind| counts[kind] == 1));
+    }
+    assert!(longest_drought(&pieces, PieceKind::Line) <= 12);
+}
+
+#[test]
+fn fourteen_bag_deals_every_piece_twice_per_bag() {
+    let pieces = deal(&mut BagRandomizer::new(2), 14 * 20);
+
+    for bag in pieces.chunks(14) {
+        let counts = count(bag);
+        assert!(PieceKind::ALL.iter().all(|kind| counts[kind] == 2));
+    }
+}
+
+#[test]
+fn history_randomizer_never_starts_with_a_square_or_z_piece() {
+    for seed in 0..50 {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let first = HistoryRandomizer::tgm().next(&mut rng);
+
+        assert!(![PieceKind::Square, PieceKind::ZLeft, PieceKind::ZRight].contains(&first));
+    }
+}
+
+#[test]
+fn history_randomizer_rarely_repeats() {
+    let with_history = deal(&mut HistoryRandomizer::tgm(), 1000);
+    let without_history = deal(&mut UniformRandomizer::new(), 1000);
+
+    let repeats = |pieces: &[PieceKind]| pieces.windows(2).filter(|w| w[0] == w[1]).count();
+    assert!(repeats(&with_history) * 4 < repeats(&without_history));
+}
+
+#[test]
+fn weighted_randomizer_follows_its_weights() {
+    let mut randomizer = WeightedRandomizer::new(vec![
+        (PieceKind::Line, 3),
+        (PieceKind::Square, 1),
+        (PieceKind::Triangle, 0),
+    ]);
+    let counts = count(&deal(&mut randomizer, 4000));
+
+    assert_eq!(counts.get(&PieceKind::Triangle), None);
+    assert_eq!(counts.len(), 2);
+    assert!(counts[&PieceKind::Line] > 2 * counts[&PieceKind::Square]);
+}
+
+#[test]
+fn uniform_randomizer_deals_every_piece() {
+    let counts = count(&deal(&mut UniformRandomizer::new(), 700));
+
+    assert!(PieceKind::ALL.iter().all(|kind| counts[kind] > 50));
+}
+
+#[test]
+fn eraser_randomizer_mixes_erasers_into_the_other_pieces() {
+    let mut randomizer = EraserRandomizer::new(Box::new(BagRandomizer::new(1)), 10);
+    let pieces = deal(&mut randomizer, 1000);
+    let erasers = count(&pieces)[&PieceKind::Eraser];
+    assert!(erasers > 60 && erasers < 140);
+
+    // the other pieces still come out of a bag
+    let others: Vec<PieceKind> = pieces.into_iter().filter(|kind| !kind.is_negative()).collect();
+    for bag in others.chunks_exact(7) {
+        let counts = count(bag);
+        assert!(PieceKind::ALL.iter().all(|kind| counts[kind] == 1));
+    }
+}
+
+#[test]
+fn randomizers_are_found_by_name() {
+    for name in &["7-bag", "14-bag", "history", "weighted", "uniform", "rescue"] {
+        assert!(from_name(name).is_some());
+    }
+    assert!(from_name("nope").is_none());
+}